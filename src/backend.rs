@@ -58,6 +58,199 @@ pub trait Backend {
     /// If the bytes failed to be written to the backend, an error variant is returned.
     /// This may mean the backend is now corrupted.
     async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error>;
+
+    /// Read all data from the backend as [`Bytes`](bytes::Bytes). Backends keeping their data in
+    /// memory as `Bytes` can hand out cheap clones, rather than copying the data on every read.
+    ///
+    /// The default implementation converts the result of [`read`](crate::backend::Backend::read).
+    ///
+    /// # Errors
+    ///
+    /// If the bytes failed to be read by the backend, an error variant is returned.
+    async fn read_bytes(&mut self) -> Result<bytes::Bytes, Self::Error> {
+        Ok(self.read().await?.into())
+    }
+
+    /// Returns an asynchronous buffered reader over all data in the backend, or `None` if the
+    /// backend does not support buffered reading. When available, the database decodes through
+    /// the reader with [`Format::from_buf_read`](crate::format::Format::from_buf_read), which
+    /// allows formats to decode incrementally without reading all data into memory first.
+    ///
+    /// The default implementation returns `None`.
+    ///
+    /// # Errors
+    ///
+    /// If the reader failed to be created, an error variant is returned.
+    async fn read_buffered(
+        &mut self,
+    ) -> Result<Option<Box<dyn tokio::io::AsyncBufRead + Send + Unpin>>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Overwrite the backend with the given data, writing it in chunks of at most `chunk_size`
+    /// bytes. After each chunk, `progress` is called with the number of bytes written so far and
+    /// the total number of bytes.
+    ///
+    /// The default implementation writes all data at once with
+    /// [`write`](crate::backend::Backend::write), and reports progress once it is done.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes failed to be written to the backend, an error variant is returned.
+    /// This may mean the backend is now corrupted.
+    async fn write_chunked(
+        &mut self,
+        data: Vec<u8>,
+        chunk_size: usize,
+        progress: &mut (dyn FnMut(u64, u64) + Send),
+    ) -> Result<(), Self::Error>
+    where
+        Self: Send,
+    {
+        let _ = chunk_size;
+        let total = data.len() as u64;
+        self.write(data).await?;
+        progress(total, total);
+        Ok(())
+    }
+
+    /// Overwrite the backend with the concatenation of the given chunks, produced as they are
+    /// written. Backends supporting this can write data without holding all of it in memory.
+    ///
+    /// A chunk that is an error means producing the data failed. The backend then stops, keeps
+    /// the data it held, and returns `Ok(())`: the error is left to the producer of the chunks.
+    ///
+    /// The default implementation collects all chunks, and writes them at once with
+    /// [`write`](crate::backend::Backend::write).
+    ///
+    /// # Errors
+    ///
+    /// If the bytes failed to be written to the backend, an error variant is returned.
+    /// This may mean the backend is now corrupted.
+    async fn write_streamed(
+        &mut self,
+        chunks: &mut (dyn Iterator<Item = std::io::Result<Vec<u8>>> + Send),
+    ) -> Result<(), Self::Error>
+    where
+        Self: Send,
+    {
+        match collect_chunks(chunks) {
+            Some(data) => self.write(data).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Returns whether the backend holds any data.
+    ///
+    /// The default implementation reads all data from the backend. Backends should override it
+    /// when a cheaper check is possible.
+    ///
+    /// # Errors
+    ///
+    /// If the backend failed to be queried, an error variant is returned.
+    async fn exists(&mut self) -> Result<bool, Self::Error>
+    where
+        Self: Send,
+    {
+        Ok(!self.read().await?.is_empty())
+    }
+
+    /// Check that the backend is reachable, without changing the data it holds. This should be
+    /// cheap, as it is meant for frequent use such as readiness probes.
+    ///
+    /// The default implementation reads all data from the backend. Backends should override it
+    /// when a cheaper check is possible.
+    ///
+    /// # Errors
+    ///
+    /// If the backend is unreachable, an error variant is returned.
+    async fn health_check(&mut self) -> Result<(), Self::Error>
+    where
+        Self: Send,
+    {
+        self.read().await.map(drop)
+    }
+}
+
+/// Concatenate the chunks of a [streamed
+/// write](crate::backend::Backend::write_streamed), or return `None` if a chunk is an error.
+fn collect_chunks(
+    chunks: &mut (dyn Iterator<Item = std::io::Result<Vec<u8>>> + Send),
+) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    for chunk in chunks {
+        data.extend_from_slice(&chunk.ok()?);
+    }
+    Some(data)
+}
+
+/// Copy all bytes from one backend to another, without decoding them.
+///
+/// # Errors
+///
+/// If the bytes failed to be read from `src` or written to `dst`, an error variant is returned.
+///
+/// # Examples
+///
+/// ```
+/// use koit::backend::{self, Backend, File, Memory};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = std::env::temp_dir().join("koit-doc-copy.json");
+///     let (mut file, _) = File::from_path_or_create(&path).await?;
+///     let mut memory = Memory::from("[1, 2, 3]");
+///
+///     backend::copy(&mut memory, &mut file).await?;
+///     assert_eq!(file.read().await?, memory.read().await?);
+///
+///     assert!(!backend::sync_if_changed(&mut memory, &mut file).await?);
+///
+///     std::fs::remove_file(&path)?;
+///     Ok(())
+/// }
+/// ```
+pub async fn copy<S, D>(src: &mut S, dst: &mut D) -> Result<(), crate::KoitError>
+where
+    S: Backend,
+    D: Backend,
+{
+    let bytes = src
+        .read()
+        .await
+        .map_err(|err| crate::KoitError::BackendRead(err.into()))?;
+    dst.write(bytes)
+        .await
+        .map_err(|err| crate::KoitError::BackendWrite(err.into()))
+}
+
+/// Copy all bytes from one backend to another, unless the other backend already holds the
+/// same bytes. Returns whether the bytes were copied.
+///
+/// # Errors
+///
+/// If the bytes failed to be read from either backend, or written to `dst`, an error variant
+/// is returned.
+pub async fn sync_if_changed<S, D>(src: &mut S, dst: &mut D) -> Result<bool, crate::KoitError>
+where
+    S: Backend,
+    D: Backend,
+{
+    let bytes = src
+        .read()
+        .await
+        .map_err(|err| crate::KoitError::BackendRead(err.into()))?;
+    let existing = dst
+        .read()
+        .await
+        .map_err(|err| crate::KoitError::BackendRead(err.into()))?;
+    if bytes == existing {
+        return Ok(false);
+    }
+    dst.write(bytes)
+        .await
+        .map_err(|err| crate::KoitError::BackendWrite(err.into()))?;
+    Ok(true)
 }
 
 /// An in-memory backend.
@@ -65,6 +258,19 @@ pub trait Backend {
 /// # Examples
 ///
 /// See the [backend module documentation](crate::backend).
+///
+/// The backend can be seeded directly from a string or byte slice, which is useful in tests.
+///
+/// ```
+/// use koit::{Database, format::Json, backend::Memory};
+///
+/// futures::executor::block_on(async move {
+///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), Memory::from("[1,2,3]"));
+///     db.reload().await.unwrap();
+///
+///     db.read(|numbers| assert_eq!(numbers, &[1, 2, 3])).await;
+/// });
+/// ```
 #[derive(std::default::Default, Debug, Clone, PartialEq, Eq)]
 pub struct Memory(Vec<u8>);
 
@@ -75,7 +281,7 @@ impl Memory {
 
     /// Take the data out of the backend, leaving an empty backend in its place.
     pub fn take(&mut self) -> Vec<u8> {
-        std::mem::replace(&mut self.0, Vec::new())
+        std::mem::take(&mut self.0)
     }
 }
 
@@ -85,6 +291,26 @@ impl From<Vec<u8>> for Memory {
     }
 }
 
+impl From<&[u8]> for Memory {
+    fn from(buf: &[u8]) -> Self {
+        Self(buf.to_vec())
+    }
+}
+
+impl From<&str> for Memory {
+    fn from(buf: &str) -> Self {
+        Self(buf.as_bytes().to_vec())
+    }
+}
+
+impl std::str::FromStr for Memory {
+    type Err = std::convert::Infallible;
+
+    fn from_str(buf: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(buf))
+    }
+}
+
 #[async_trait]
 impl Backend for Memory {
     type Error = std::convert::Infallible;
@@ -93,95 +319,2437 @@ impl Backend for Memory {
         Ok(self.0.clone())
     }
     async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
-        Ok(self.0 = data)
+        self.0 = data;
+        Ok(())
+    }
+    async fn exists(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.0.is_empty())
+    }
+    async fn health_check(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// An in-memory backend sharing its data through [`Bytes`](bytes::Bytes).
+///
+/// Unlike [`Memory`](crate::backend::Memory),
+/// [`read_bytes`](crate::backend::Backend::read_bytes) does not copy the data: all reads share
+/// the same allocation until the next write.
+///
+/// # Examples
+///
+/// ```
+/// use koit::backend::{Backend, BytesMemory};
+///
+/// futures::executor::block_on(async {
+///     let mut backend = BytesMemory::from("[1, 2, 3]");
+///
+///     let a = backend.read_bytes().await.unwrap();
+///     let b = backend.read_bytes().await.unwrap();
+///     assert_eq!(a, b);
+///     assert_eq!(a.as_ptr(), b.as_ptr());
+/// });
+/// ```
+#[derive(std::default::Default, Debug, Clone, PartialEq, Eq)]
+pub struct BytesMemory(bytes::Bytes);
+
+impl BytesMemory {
+    pub fn new() -> Self {
+        Self(bytes::Bytes::new())
+    }
+
+    /// Take the data out of the backend, leaving an empty backend in its place.
+    pub fn take(&mut self) -> bytes::Bytes {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl From<bytes::Bytes> for BytesMemory {
+    fn from(buf: bytes::Bytes) -> Self {
+        Self(buf)
+    }
+}
+
+impl From<Vec<u8>> for BytesMemory {
+    fn from(buf: Vec<u8>) -> Self {
+        Self(buf.into())
+    }
+}
+
+impl From<&'static str> for BytesMemory {
+    fn from(buf: &'static str) -> Self {
+        Self(buf.into())
+    }
+}
+
+#[async_trait]
+impl Backend for BytesMemory {
+    type Error = std::convert::Infallible;
+
+    async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.0.to_vec())
+    }
+    async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.0 = data.into();
+        Ok(())
+    }
+    async fn read_bytes(&mut self) -> Result<bytes::Bytes, Self::Error> {
+        Ok(self.0.clone())
+    }
+    async fn exists(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.0.is_empty())
+    }
+    async fn health_check(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// An in-memory backend whose data can be observed through shared handles.
+///
+/// Clones of the backend share the same data, so a clone kept outside the database sees every
+/// save, without taking the backend back through
+/// [`into_parts`](crate::Database::into_parts).
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use koit::{Database, format::Json, backend::SharedMemory};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let backend = SharedMemory::new();
+///     let db: Arc<Database<Vec<u32>, _, Json>> =
+///         Arc::new(Database::from_parts(Vec::new(), backend.clone()));
+///
+///     let writer = tokio::spawn({
+///         let db = db.clone();
+///         async move {
+///             for n in 0..10 {
+///                 db.write(|numbers| numbers.push(n)).await;
+///                 db.save().await.unwrap();
+///             }
+///         }
+///     });
+///     while !writer.is_finished() {
+///         // Every observed state is a complete save.
+///         let bytes = backend.buffer();
+///         if !bytes.is_empty() {
+///             serde_json::from_slice::<Vec<u32>>(&bytes).unwrap();
+///         }
+///         tokio::task::yield_now().await;
+///     }
+///     writer.await.unwrap();
+///
+///     let saved: Vec<u32> = serde_json::from_slice(&backend.buffer()).unwrap();
+///     assert_eq!(saved, (0..10).collect::<Vec<_>>());
+/// }
+/// ```
+#[derive(std::default::Default, Debug, Clone)]
+pub struct SharedMemory(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl SharedMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the data currently in the backend.
+    pub fn buffer(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl From<Vec<u8>> for SharedMemory {
+    fn from(buf: Vec<u8>) -> Self {
+        Self(std::sync::Arc::new(std::sync::Mutex::new(buf)))
+    }
+}
+
+impl From<&str> for SharedMemory {
+    fn from(buf: &str) -> Self {
+        Self::from(buf.as_bytes().to_vec())
+    }
+}
+
+#[async_trait]
+impl Backend for SharedMemory {
+    type Error = std::convert::Infallible;
+
+    async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.buffer())
+    }
+    async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+        *self.0.lock().unwrap() = data;
+        Ok(())
+    }
+    async fn exists(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.0.lock().unwrap().is_empty())
+    }
+    async fn health_check(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// The error the [`Static`](crate::backend::Static) backend returns on writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("the backend is read-only")]
+pub struct ReadOnly;
+
+/// Writing to a read-only backend never succeeds.
+impl RetryableError for ReadOnly {
+    fn is_retryable(&self) -> bool {
+        false
+    }
+}
+
+/// A read-only backend over bytes with a static lifetime, such as default data compiled into
+/// the binary with [`include_bytes!`]. Reads return the bytes without copying them through
+/// [`read_bytes`](crate::backend::Backend::read_bytes); writes fail with
+/// [`ReadOnly`](crate::backend::ReadOnly).
+///
+/// # Examples
+///
+/// ```
+/// use koit::{Database, format::Json, backend::{ReadOnly, Static}};
+///
+/// // Usually `include_bytes!("defaults.json")`.
+/// static DEFAULTS: &[u8] = b"[1, 2, 3]";
+///
+/// futures::executor::block_on(async {
+///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), Static(DEFAULTS));
+///     db.reload().await.unwrap();
+///     assert_eq!(db.read(Vec::clone).await, vec![1, 2, 3]);
+///
+///     let err = db.save().await.unwrap_err();
+///     assert!(std::error::Error::source(&err).unwrap().is::<ReadOnly>());
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Static(pub &'static [u8]);
+
+#[async_trait]
+impl Backend for Static {
+    type Error = ReadOnly;
+
+    async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.0.to_vec())
+    }
+    async fn write(&mut self, _data: Vec<u8>) -> Result<(), Self::Error> {
+        Err(ReadOnly)
+    }
+    async fn read_bytes(&mut self) -> Result<bytes::Bytes, Self::Error> {
+        Ok(bytes::Bytes::from_static(self.0))
+    }
+    async fn exists(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.0.is_empty())
+    }
+    async fn health_check(&mut self) -> Result<(), Self::Error> {
+        Ok(())
     }
 }
 
+pub use self::layer::{ChecksumError, Checksummed, Layer, Retry, RetryableError};
+
 #[cfg(feature = "file-backend")]
-pub use self::file::File;
+pub use self::file::{File, FileBuilder, FileError, SyncPolicy};
 
 #[cfg(feature = "file-backend")]
-mod file {
+pub(crate) use self::file::{with_suffix, with_unique_suffix};
+
+#[cfg(feature = "file-backend")]
+pub use self::journaling::JournalingFile;
+
+#[cfg(feature = "file-backend")]
+pub use self::atomic::AtomicFile;
+
+#[cfg(feature = "file-backend")]
+pub use self::snapshot_dir::SnapshotDir;
+
+#[cfg(feature = "stream-backend")]
+pub use self::stream::Stream;
+
+#[cfg(all(unix, feature = "unix-socket-backend"))]
+pub use self::unix_socket::UnixSocket;
+
+mod layer {
+    use std::convert::TryInto;
+
     use async_trait::async_trait;
-    use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+    use thiserror::Error;
 
     use super::Backend;
+    use crate::crc32;
 
-    /// A file-backed backend.
+    /// Fluent construction of layered backends, implemented for all backends.
     ///
-    /// Note: this requires its futures to be executed on the Tokio 0.3 runtime.
-    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
-    #[derive(Debug)]
-    pub struct File(tokio::fs::File);
-
-    impl File {
-        /// Creates the backend by opening the file at the given path.
-        ///
-        /// # Errors
-        ///
-        /// If the file does not exist or could not be opened for reading and writing, an error
-        /// variant is returned.
-        pub async fn from_path<P>(path: P) -> Result<Self, std::io::Error>
-        where
-            P: AsRef<std::path::Path>,
-        {
-            Ok(Self(
-                tokio::fs::OpenOptions::new()
-                    .read(true)
-                    .write(true)
-                    .open(path)
-                    .await?,
-            ))
+    /// Each method wraps the backend in another backend adding a feature, so layers can be
+    /// stacked without spelling out the nested types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::{Layer, Memory}};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let backend = Memory::default().checksummed();
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1, 2], backend);
+    ///
+    ///     db.save().await.unwrap();
+    ///     db.replace(Vec::new()).await;
+    ///     db.reload().await.unwrap();
+    ///     db.read(|numbers| assert_eq!(numbers, &[1, 2])).await;
+    /// });
+    /// ```
+    pub trait Layer: Backend + Sized {
+        /// Wrap the backend in a [`Checksummed`](crate::backend::Checksummed) backend.
+        fn checksummed(self) -> Checksummed<Self> {
+            Checksummed::new(self)
         }
 
-        /// Creates the backend by opening a file at the given path. Creates the file if it
-        /// does not exist yet.
-        ///
-        /// # Errors
-        ///
-        /// If the file does not exist, but could not be created, or could not be opened for
-        /// reading and writing, an error variant is returned.
-        pub async fn from_path_or_create<P>(path: P) -> Result<(Self, bool), std::io::Error>
+        /// Wrap the backend in a [`Retry`](crate::backend::Retry) backend, making up to
+        /// `attempts` attempts per operation.
+        fn retrying(self, attempts: u32) -> Retry<Self>
         where
-            P: AsRef<std::path::Path>,
+            Self::Error: RetryableError,
         {
-            let backend = Self::from_path(&path).await;
-            match backend {
-                Ok(self_) => Ok((self_, true)),
-                Err(err) => match err.kind() {
-                    std::io::ErrorKind::NotFound => Ok((
-                        Self(
-                            tokio::fs::OpenOptions::new()
-                                .read(true)
-                                .write(true)
-                                .create(true)
-                                .open(&path)
-                                .await?,
-                        ),
-                        false,
-                    )),
-                    _ => Err(err),
-                },
-            }
+            Retry::new(self, attempts)
         }
     }
 
-    #[async_trait]
-    impl Backend for File {
-        type Error = std::io::Error;
+    impl<B: Backend> Layer for B {}
 
-        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
-            let mut buffer = Vec::new();
-            self.0.seek(std::io::SeekFrom::Start(0)).await?;
-            self.0.read_to_end(&mut buffer).await?;
-            Ok(buffer)
+    /// The error variants the [`Checksummed`](crate::backend::Checksummed) backend can return.
+    #[derive(Debug, Error)]
+    pub enum ChecksumError<E> {
+        /// The inner backend failed.
+        #[error(transparent)]
+        Backend(E),
+        /// The stored data is too short to contain a checksum.
+        #[error("the data is too short to contain a checksum")]
+        Truncated,
+        /// The stored checksum does not match the data.
+        #[error("checksum mismatch: expected {expected:#010x}, found {found:#010x}")]
+        Mismatch { expected: u32, found: u32 },
+    }
+
+    /// A backend storing a CRC-32 checksum after the data in the inner backend, detecting
+    /// corruption when reading.
+    ///
+    /// The checksum is stored as four little-endian bytes. An empty inner backend reads as no
+    /// bytes, so a backend that was never written to can be used as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::backend::{Backend, Checksummed, ChecksumError, Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let mut backend = Checksummed::new(Memory::default());
+    ///     backend.write(b"123456789".to_vec()).await.unwrap();
+    ///     assert_eq!(backend.inner().clone().take(), b"123456789\x26\x39\xf4\xcb");
+    ///
+    ///     backend.write(b"[1, 2]".to_vec()).await.unwrap();
+    ///     assert_eq!(backend.read().await.unwrap(), b"[1, 2]");
+    ///
+    ///     let mut bytes = backend.into_inner().take();
+    ///     bytes[1] = b'7';
+    ///     let mut backend = Checksummed::new(Memory::from(bytes));
+    ///     assert!(matches!(backend.read().await, Err(ChecksumError::Mismatch { .. })));
+    /// });
+    /// ```
+    #[derive(Debug, Clone, std::default::Default)]
+    pub struct Checksummed<B> {
+        inner: B,
+    }
+
+    impl<B> Checksummed<B> {
+        pub fn new(inner: B) -> Self {
+            Self { inner }
         }
 
-        async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
-            self.0.seek(std::io::SeekFrom::Start(0)).await?;
-            self.0.set_len(0).await?;
-            self.0.write_all(&data).await?;
-            self.0.sync_all().await?;
+        /// Returns a reference to the inner backend.
+        pub fn inner(&self) -> &B {
+            &self.inner
+        }
+
+        /// Consume the backend and return the inner backend.
+        pub fn into_inner(self) -> B {
+            self.inner
+        }
+    }
+
+    /// Classification of backend errors into transient errors, for which retrying the operation
+    /// may succeed, and permanent errors.
+    ///
+    /// Backends can implement this for their error type to be usable with
+    /// [`Retry`](crate::backend::Retry) without a custom classifier.
+    pub trait RetryableError {
+        /// Returns whether retrying the failed operation may succeed.
+        fn is_retryable(&self) -> bool;
+    }
+
+    /// Interrupted operations, operations that would block, and timeouts are retryable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Error, ErrorKind};
+    /// use koit::backend::RetryableError;
+    ///
+    /// assert!(Error::from(ErrorKind::Interrupted).is_retryable());
+    /// assert!(Error::from(ErrorKind::WouldBlock).is_retryable());
+    /// assert!(Error::from(ErrorKind::TimedOut).is_retryable());
+    /// assert!(!Error::from(ErrorKind::NotFound).is_retryable());
+    /// ```
+    impl RetryableError for std::io::Error {
+        fn is_retryable(&self) -> bool {
+            matches!(
+                self.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::TimedOut
+            )
+        }
+    }
+
+    impl RetryableError for std::convert::Infallible {
+        fn is_retryable(&self) -> bool {
+            match *self {}
+        }
+    }
+
+    /// Only errors of the inner backend can be retryable; corrupt data stays corrupt.
+    impl<E> RetryableError for ChecksumError<E>
+    where
+        E: RetryableError,
+    {
+        fn is_retryable(&self) -> bool {
+            match self {
+                ChecksumError::Backend(err) => err.is_retryable(),
+                ChecksumError::Truncated | ChecksumError::Mismatch { .. } => false,
+            }
+        }
+    }
+
+    /// A backend retrying failed reads and writes of the inner backend, as long as the errors
+    /// are classified as retryable. Operations are attempted at most a given number of times, the
+    /// last error being returned if all attempts fail.
+    ///
+    /// Errors are classified by their [`RetryableError`](crate::backend::RetryableError)
+    /// implementation, or by a custom classifier given through
+    /// [`with_classifier`](crate::backend::Retry::with_classifier).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use koit::backend::{Backend, Layer, Retry};
+    ///
+    /// /// A backend failing the given number of times before each success.
+    /// struct Flaky {
+    ///     kind: std::io::ErrorKind,
+    ///     failures: u32,
+    ///     remaining: u32,
+    /// }
+    ///
+    /// #[async_trait]
+    /// impl Backend for Flaky {
+    ///     type Error = std::io::Error;
+    ///
+    ///     async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+    ///         if self.remaining > 0 {
+    ///             self.remaining -= 1;
+    ///             return Err(self.kind.into());
+    ///         }
+    ///         self.remaining = self.failures;
+    ///         Ok(b"[]".to_vec())
+    ///     }
+    ///
+    ///     async fn write(&mut self, _data: Vec<u8>) -> Result<(), Self::Error> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let flaky = |kind| Flaky { kind, failures: 2, remaining: 2 };
+    ///
+    ///     // Interruptions are retryable by default.
+    ///     let mut backend = flaky(std::io::ErrorKind::Interrupted).retrying(3);
+    ///     assert_eq!(backend.read().await.unwrap(), b"[]");
+    ///     let mut backend = flaky(std::io::ErrorKind::Interrupted).retrying(2);
+    ///     assert!(backend.read().await.is_err());
+    ///
+    ///     // Other errors are not, unless a custom classifier says so.
+    ///     let mut backend = flaky(std::io::ErrorKind::Other).retrying(3);
+    ///     assert!(backend.read().await.is_err());
+    ///     let mut backend = Retry::with_classifier(flaky(std::io::ErrorKind::Other), 3, |err| {
+    ///         err.kind() == std::io::ErrorKind::Other
+    ///     });
+    ///     assert_eq!(backend.read().await.unwrap(), b"[]");
+    /// });
+    /// ```
+    #[derive(Debug)]
+    pub struct Retry<B>
+    where
+        B: Backend,
+    {
+        inner: B,
+        attempts: u32,
+        is_retryable: fn(&B::Error) -> bool,
+    }
+
+    impl<B> Retry<B>
+    where
+        B: Backend,
+    {
+        /// Wrap the backend, making up to `attempts` attempts per operation. At least one attempt
+        /// is always made.
+        pub fn new(inner: B, attempts: u32) -> Self
+        where
+            B::Error: RetryableError,
+        {
+            Self::with_classifier(inner, attempts, RetryableError::is_retryable)
+        }
+
+        /// Wrap the backend, making up to `attempts` attempts per operation, and classifying
+        /// errors as retryable by the given function.
+        pub fn with_classifier(
+            inner: B,
+            attempts: u32,
+            is_retryable: fn(&B::Error) -> bool,
+        ) -> Self {
+            Self {
+                inner,
+                attempts: attempts.max(1),
+                is_retryable,
+            }
+        }
+
+        /// Returns a reference to the inner backend.
+        pub fn inner(&self) -> &B {
+            &self.inner
+        }
+
+        /// Consume the backend and return the inner backend.
+        pub fn into_inner(self) -> B {
+            self.inner
+        }
+
+        /// Whether the operation should be attempted again after the given failed attempt,
+        /// counting from one.
+        fn should_retry(&self, attempt: u32, err: &B::Error) -> bool {
+            attempt < self.attempts && (self.is_retryable)(err)
+        }
+    }
+
+    #[async_trait]
+    impl<B> Backend for Retry<B>
+    where
+        B: Backend + Send,
+    {
+        type Error = B::Error;
+
+        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+            let mut attempt = 1;
+            loop {
+                match self.inner.read().await {
+                    Err(err) if self.should_retry(attempt, &err) => attempt += 1,
+                    result => return result,
+                }
+            }
+        }
+
+        async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+            let mut attempt = 1;
+            loop {
+                // The inner backend consumes the data, so every attempt but the last writes a copy.
+                let result = if attempt < self.attempts {
+                    self.inner.write(data.clone()).await
+                } else {
+                    return self.inner.write(data).await;
+                };
+                match result {
+                    Err(err) if self.should_retry(attempt, &err) => attempt += 1,
+                    result => return result,
+                }
+            }
+        }
+    }
+
+    #[async_trait]
+    impl<B> Backend for Checksummed<B>
+    where
+        B: Backend + Send,
+    {
+        type Error = ChecksumError<B::Error>;
+
+        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+            let mut data = self.inner.read().await.map_err(ChecksumError::Backend)?;
+            if data.is_empty() {
+                return Ok(data);
+            }
+            if data.len() < 4 {
+                return Err(ChecksumError::Truncated);
+            }
+            let stored = data.split_off(data.len() - 4);
+            let expected = u32::from_le_bytes(stored[..].try_into().unwrap());
+            let found = crc32::checksum(&data);
+            if expected != found {
+                return Err(ChecksumError::Mismatch { expected, found });
+            }
+            Ok(data)
+        }
+
+        async fn write(&mut self, mut data: Vec<u8>) -> Result<(), Self::Error> {
+            let checksum = crc32::checksum(&data);
+            data.extend_from_slice(&checksum.to_le_bytes());
+            self.inner.write(data).await.map_err(ChecksumError::Backend)
+        }
+    }
+}
+
+#[cfg(feature = "file-backend")]
+mod file {
+    use std::convert::TryFrom;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use async_trait::async_trait;
+    use thiserror::Error;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+    use super::Backend;
+
+    /// Append a suffix to the file name of the path.
+    pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+        let mut file_name = path.file_name().unwrap_or_default().to_owned();
+        file_name.push(suffix);
+        path.with_file_name(file_name)
+    }
+
+    /// Append a suffix to the file name of the path that is unique to this call, such that the
+    /// path cannot clash with other temporary files, and ends in the given suffix.
+    pub(crate) fn with_unique_suffix(path: &Path, suffix: &str) -> PathBuf {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        with_suffix(
+            path,
+            &format!(".{}-{}{}", std::process::id(), count, suffix),
+        )
+    }
+
+    /// Write all of the data to the file, adding the number of bytes written to `written` as
+    /// they are confirmed. The file must not grow beyond `limit` bytes, if given.
+    async fn write_counted(
+        file: &mut tokio::fs::File,
+        mut data: &[u8],
+        written: &mut u64,
+        limit: Option<u64>,
+    ) -> Result<(), std::io::Error> {
+        while !data.is_empty() {
+            let room = limit.map_or(data.len() as u64, |limit| limit.saturating_sub(*written));
+            if room == 0 {
+                return Err(std::io::Error::other("the write limit was reached"));
+            }
+            let len = data.len().min(usize::try_from(room).unwrap_or(usize::MAX));
+            let count = file.write(&data[..len]).await?;
+            if count == 0 {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            // Tokio writes in the background, and reports errors on the next operation. The
+            // bytes are only known to be written once flushed.
+            file.flush().await?;
+            *written += count as u64;
+            data = &data[count..];
+        }
+        Ok(())
+    }
+
+    /// The error variants the [`File`](crate::backend::File) backend can return.
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+    #[derive(Debug, Error)]
+    pub enum FileError {
+        /// An I/O operation on the file failed.
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        /// The file is larger than the configured read limit.
+        #[error("the file is larger than the read limit of {limit} bytes")]
+        TooLarge { limit: u64 },
+        /// The file was [closed](crate::backend::File::close).
+        #[error("the file is closed")]
+        Closed,
+        /// Writing failed part-way, after `written` bytes of the new data were written. The file
+        /// was truncated before writing, so it now holds only those bytes, and is likely corrupt.
+        /// Use [`AtomicFile`](crate::backend::AtomicFile) to rule this out.
+        ///
+        /// With the `testing` feature, failed writes can be simulated using
+        /// `File::with_write_limit`.
+        #[error("the write failed after {written} bytes")]
+        PartialWrite {
+            written: u64,
+            #[source]
+            source: std::io::Error,
+        },
+    }
+
+    /// I/O errors are classified as [`std::io::Error`] is; the other errors are not retryable.
+    impl super::RetryableError for FileError {
+        fn is_retryable(&self) -> bool {
+            match self {
+                FileError::Io(err) | FileError::PartialWrite { source: err, .. } => {
+                    err.is_retryable()
+                }
+                FileError::TooLarge { .. } | FileError::Closed => false,
+            }
+        }
+    }
+
+    /// A file-backed backend.
+    ///
+    /// Note: this requires its futures to be executed on the Tokio runtime.
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+    #[derive(Debug)]
+    pub struct File {
+        file: Option<tokio::fs::File>,
+        path: PathBuf,
+        read_limit: Option<u64>,
+        sync: bool,
+        atomic: bool,
+        buffer: Option<WriteBuffer>,
+        disk_writes: u64,
+        lazy: bool,
+        options: Option<tokio::fs::OpenOptions>,
+        #[cfg(feature = "testing")]
+        write_limit: Option<u64>,
+    }
+
+    /// Writes held back by a [buffered](crate::backend::File::buffered) file.
+    #[derive(Debug)]
+    struct WriteBuffer {
+        capacity: usize,
+        buffered: usize,
+        pending: Option<Vec<u8>>,
+    }
+
+    impl File {
+        fn new(file: tokio::fs::File, path: PathBuf) -> Self {
+            Self {
+                file: Some(file),
+                ..Self::lazy(path)
+            }
+        }
+
+        /// Creates the backend without opening the file. The file is created on the first
+        /// write; until then, reads return no bytes.
+        pub(crate) fn lazy(path: PathBuf) -> Self {
+            Self {
+                file: None,
+                path,
+                read_limit: None,
+                sync: true,
+                atomic: false,
+                buffer: None,
+                disk_writes: 0,
+                lazy: true,
+                options: None,
+                #[cfg(feature = "testing")]
+                write_limit: None,
+            }
+        }
+
+        /// Whether the file is yet to be created by the first write.
+        fn is_unopened(&self) -> bool {
+            self.lazy && self.file.is_none()
+        }
+
+        /// Set whether writes are flushed to disk before completing, which is the default.
+        ///
+        /// Disabling this makes writes considerably faster, for example in test suites, but
+        /// written data may be lost or corrupted on a crash or power loss. Only disable it when
+        /// durability does not matter.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::backend::{Backend, File};
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-with-sync.json");
+        ///     let (backend, _) = File::from_path_or_create(&path).await?;
+        ///     let mut backend = backend.with_sync(false);
+        ///
+        ///     backend.write(b"[1, 2, 3]".to_vec()).await?;
+        ///     backend.write(b"[1]".to_vec()).await?;
+        ///     assert_eq!(backend.read().await?, b"[1]");
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        pub fn with_sync(mut self, sync: bool) -> Self {
+            self.sync = sync;
+            self
+        }
+
+        /// Make writes fail once the file would grow beyond `limit` bytes, as if the disk were
+        /// full, for testing how failed writes are handled. The bytes up to the limit are written
+        /// before the write fails.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::backend::{Backend, File, FileError};
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-with-write-limit.json");
+        ///     let (mut backend, _) = File::from_path_or_create(&path).await?;
+        ///     backend.write(b"[4, 5, 6, 7, 8]".to_vec()).await?;
+        ///
+        ///     let mut backend = backend.with_write_limit(4);
+        ///     let result = backend.write(b"[1, 2, 3]".to_vec()).await;
+        ///     assert!(matches!(result, Err(FileError::PartialWrite { written: 4, .. })));
+        ///     assert_eq!(std::fs::read(&path)?, b"[1, ");
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        #[cfg(feature = "testing")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+        pub fn with_write_limit(mut self, limit: u64) -> Self {
+            self.write_limit = Some(limit);
+            self
+        }
+
+        /// The limit set by `with_write_limit`, if any.
+        fn write_limit(&self) -> Option<u64> {
+            #[cfg(feature = "testing")]
+            return self.write_limit;
+            #[cfg(not(feature = "testing"))]
+            None
+        }
+
+        /// Returns the path of the backing file, as given when the backend was created.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::backend::File;
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-path.json");
+        ///     std::fs::write(&path, "[]")?;
+        ///
+        ///     let backend = File::from_path(&path).await?;
+        ///     assert_eq!(backend.path(), path);
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// The path of a temporary file next to the backing file, ending in the given suffix. The
+        /// path is unique to this call, so it cannot clash with other temporary files.
+        pub(crate) fn unique_temp_path(&self, suffix: &str) -> PathBuf {
+            with_unique_suffix(&self.path, suffix)
+        }
+
+        /// Copy the backing file to a backup next to it, returning the path of the backup, or
+        /// `None` if the backing file does not exist yet.
+        pub(crate) async fn back_up(&self) -> Result<Option<PathBuf>, std::io::Error> {
+            let backup_path = self.unique_temp_path(".bak");
+            match tokio::fs::copy(&self.path, &backup_path).await {
+                Ok(_) => Ok(Some(backup_path)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err),
+            }
+        }
+
+        /// Remove the backing file, such that the backend holds no data until it is written to
+        /// again.
+        pub(crate) async fn remove(&mut self) -> Result<(), std::io::Error> {
+            self.file = None;
+            self.lazy = true;
+            match tokio::fs::remove_file(&self.path).await {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            }
+        }
+
+        /// Atomically replace the backing file by the file at `source`, by renaming it into
+        /// place. The backend continues with the new file.
+        pub(crate) async fn replace_with(&mut self, source: &Path) -> Result<(), std::io::Error> {
+            tokio::fs::rename(source, &self.path).await?;
+            self.file = Some(
+                tokio::fs::OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(&self.path)
+                    .await?,
+            );
+            if let Some(buffer) = self.buffer.as_mut() {
+                buffer.pending = None;
+                buffer.buffered = 0;
+            }
+            Ok(())
+        }
+
+        /// Open the file at the backend's path again, replacing the current handle. This allows
+        /// continuing after [`close`](crate::backend::File::close), or after the file was replaced
+        /// by another process, for example by log rotation.
+        ///
+        /// The file is opened with the options the backend was created with; by default, for
+        /// reading and writing. Writes held back by a [buffered](crate::backend::File::buffered)
+        /// backend are kept, and written to the reopened file.
+        ///
+        /// # Errors
+        ///
+        /// If the file could not be opened, an error variant is returned. The current handle is
+        /// kept.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::backend::{Backend, File};
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-reopen.json");
+        ///     std::fs::write(&path, "[1]")?;
+        ///     let mut backend = File::from_path(&path).await?;
+        ///
+        ///     backend.close().await?;
+        ///     assert!(backend.read().await.is_err());
+        ///
+        ///     backend.reopen().await?;
+        ///     assert_eq!(backend.read().await?, b"[1]");
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        pub async fn reopen(&mut self) -> Result<(), std::io::Error> {
+            let file = match &self.options {
+                Some(options) => options.open(&self.path).await?,
+                None => {
+                    tokio::fs::OpenOptions::new()
+                        .read(true)
+                        .write(true)
+                        .open(&self.path)
+                        .await?
+                }
+            };
+            self.file = Some(file);
+            self.lazy = false;
+            Ok(())
+        }
+
+        /// Returns the open file handle.
+        fn handle(&mut self) -> Result<&mut tokio::fs::File, FileError> {
+            self.file.as_mut().ok_or(FileError::Closed)
+        }
+
+        /// Close the file, releasing its handle. Subsequent reads and writes fail with
+        /// [`FileError::Closed`](crate::backend::FileError::Closed). Closing an already closed
+        /// file does nothing.
+        ///
+        /// This is useful on platforms where open files cannot be renamed or deleted.
+        ///
+        /// # Errors
+        ///
+        /// If pending writes failed to be flushed, an error variant is returned. The file is
+        /// closed regardless, and the pending writes are lost.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::backend::{Backend, File, FileError};
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-close.json");
+        ///     let (mut backend, _) = File::from_path_or_create(&path).await?;
+        ///
+        ///     backend.close().await?;
+        ///     assert!(matches!(backend.read().await, Err(FileError::Closed)));
+        ///     assert!(matches!(backend.write(b"[]".to_vec()).await, Err(FileError::Closed)));
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        pub async fn close(&mut self) -> Result<(), FileError> {
+            let flushed = self.flush().await;
+            self.buffer = None;
+            self.lazy = false;
+            match self.file.take() {
+                Some(mut file) => {
+                    flushed?;
+                    Ok(file.flush().await?)
+                }
+                None => Ok(()),
+            }
+        }
+
+        /// Creates the backend by opening the file at the given path, holding back writes until
+        /// at least `capacity` bytes have been written.
+        ///
+        /// As every write replaces the entire contents, only the most recent write is kept in
+        /// memory and written to the file once the capacity is reached, or when
+        /// [`flush`](crate::backend::File::flush) or [`close`](crate::backend::File::close) is
+        /// called. Reads return the most recent write, whether it was written to the file or
+        /// not. Writes still held back when the backend is dropped are lost.
+        ///
+        /// The [sync setting](crate::backend::File::with_sync) applies when writes reach the
+        /// file: with syncing enabled, a flush only completes once the data is on disk, but data
+        /// that is held back is not durable at all.
+        ///
+        /// # Errors
+        ///
+        /// If the file does not exist or could not be opened for reading and writing, an error
+        /// variant is returned.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::backend::{Backend, File};
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-buffered.json");
+        ///     std::fs::write(&path, "[]")?;
+        ///     let mut backend = File::buffered(&path, 1024).await?;
+        ///
+        ///     for n in 1..=5 {
+        ///         backend.write(format!("[{}]", n).into_bytes()).await?;
+        ///     }
+        ///     assert_eq!(backend.read().await?, b"[5]");
+        ///     assert_eq!(std::fs::read(&path)?, b"[]");
+        ///
+        ///     backend.flush().await?;
+        ///     assert_eq!(std::fs::read(&path)?, b"[5]");
+        ///     assert_eq!(backend.disk_writes(), 1);
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        pub async fn buffered<P>(path: P, capacity: usize) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            let mut backend = Self::from_path(path).await?;
+            backend.buffer = Some(WriteBuffer {
+                capacity,
+                buffered: 0,
+                pending: None,
+            });
+            Ok(backend)
+        }
+
+        /// Write the most recent write held back by a
+        /// [buffered](crate::backend::File::buffered) file to the file. Does nothing if no write
+        /// is held back.
+        ///
+        /// # Errors
+        ///
+        /// If the file failed to be written, an error variant is returned. The write remains held
+        /// back.
+        pub async fn flush(&mut self) -> Result<(), FileError> {
+            let pending = match self
+                .buffer
+                .as_mut()
+                .and_then(|buffer| buffer.pending.take())
+            {
+                Some(pending) => pending,
+                None => return Ok(()),
+            };
+            if let Err(err) = self.write_through(&pending).await {
+                if let Some(buffer) = self.buffer.as_mut() {
+                    buffer.pending = Some(pending);
+                }
+                return Err(err);
+            }
+            if let Some(buffer) = self.buffer.as_mut() {
+                buffer.buffered = 0;
+            }
+            Ok(())
+        }
+
+        /// Returns the number of times data was written to the file by this backend.
+        pub fn disk_writes(&self) -> u64 {
+            self.disk_writes
+        }
+
+        /// Overwrite the file with the given data.
+        async fn write_through(&mut self, data: &[u8]) -> Result<(), FileError> {
+            if self.atomic {
+                return self.write_replacing(data).await;
+            }
+            self.write_through_chunked(data, data.len().max(1), &mut |_, _| {})
+                .await
+        }
+
+        /// Overwrite the file with the given data in chunks, reporting progress after each.
+        async fn write_through_chunked(
+            &mut self,
+            data: &[u8],
+            chunk_size: usize,
+            progress: &mut (dyn FnMut(u64, u64) + Send),
+        ) -> Result<(), FileError> {
+            let total = data.len() as u64;
+            let chunks = data.chunks(chunk_size.max(1));
+            self.write_through_chunks(chunks, &mut |written| progress(written, total))
+                .await
+        }
+
+        /// Truncate the file and write the chunks to it, calling `progress` with the number of
+        /// bytes written so far after each chunk.
+        async fn write_through_chunks<I>(
+            &mut self,
+            chunks: I,
+            progress: &mut (dyn FnMut(u64) + Send),
+        ) -> Result<(), FileError>
+        where
+            I: Iterator + Send,
+            I::Item: AsRef<[u8]> + Send,
+        {
+            let sync = self.sync;
+            let limit = self.write_limit();
+            let file = self.handle()?;
+            file.seek(std::io::SeekFrom::Start(0)).await?;
+            file.set_len(0).await?;
+            let mut written = 0;
+            let result = async {
+                for chunk in chunks {
+                    write_counted(file, chunk.as_ref(), &mut written, limit).await?;
+                    progress(written);
+                }
+                Ok(())
+            };
+            if let Err(source) = result.await {
+                return Err(FileError::PartialWrite { written, source });
+            }
+            if sync {
+                file.sync_all().await?;
+            }
+            self.disk_writes += 1;
+            Ok(())
+        }
+
+        /// Write the data to a temporary file next to the backing file, and rename it into place.
+        async fn write_replacing(&mut self, data: &[u8]) -> Result<(), FileError> {
+            let temp_path = self.unique_temp_path(".tmp");
+            self.write_via(&temp_path, data).await
+        }
+
+        /// Write the data to the temporary file at `temp_path`, flushing it to disk if writes are
+        /// synced, and rename it into place. The temporary file is removed if this fails.
+        ///
+        /// Renaming is only atomic within a filesystem, so `temp_path` must be on the same
+        /// filesystem as the backing file.
+        pub(crate) async fn write_via(
+            &mut self,
+            temp_path: &Path,
+            data: &[u8],
+        ) -> Result<(), FileError> {
+            let written = if self.sync {
+                crate::write_synced(temp_path, data).await
+            } else {
+                tokio::fs::write(temp_path, data).await
+            };
+            if let Err(err) = written {
+                let _ = tokio::fs::remove_file(temp_path).await;
+                return Err(err.into());
+            }
+            if let Err(err) = self.replace_with(temp_path).await {
+                let _ = tokio::fs::remove_file(temp_path).await;
+                return Err(err.into());
+            }
+            self.disk_writes += 1;
+            Ok(())
+        }
+
+        /// Write the chunks to the temporary file at `temp_path` as they are produced, flushing it
+        /// to disk if writes are synced, and rename it into place. The temporary file is removed
+        /// if a chunk is an error, or if this fails; the backing file is then left as it was.
+        async fn stream_via(
+            &mut self,
+            temp_path: &Path,
+            chunks: &mut (dyn Iterator<Item = std::io::Result<Vec<u8>>> + Send),
+        ) -> Result<(), FileError> {
+            let sync = self.sync;
+            let limit = self.write_limit();
+            let written: Result<bool, std::io::Error> = async {
+                let mut file = tokio::fs::File::create(temp_path).await?;
+                let mut written = 0;
+                for chunk in chunks {
+                    match chunk {
+                        Ok(chunk) => write_counted(&mut file, &chunk, &mut written, limit).await?,
+                        Err(_) => return Ok(false),
+                    }
+                }
+                if sync {
+                    file.sync_all().await?;
+                }
+                Ok(true)
+            }
+            .await;
+            match written {
+                Ok(true) => {}
+                Ok(false) => {
+                    let _ = tokio::fs::remove_file(temp_path).await;
+                    return Ok(());
+                }
+                Err(err) => {
+                    let _ = tokio::fs::remove_file(temp_path).await;
+                    return Err(err.into());
+                }
+            }
+            if let Err(err) = self.replace_with(temp_path).await {
+                let _ = tokio::fs::remove_file(temp_path).await;
+                return Err(err.into());
+            }
+            self.disk_writes += 1;
+            Ok(())
+        }
+
+        /// Open the file if it is not yet opened, creating it if it does not exist.
+        async fn open_for_write(&mut self) -> Result<(), FileError> {
+            if self.is_unopened() {
+                self.file = Some(
+                    tokio::fs::OpenOptions::new()
+                        .read(true)
+                        .write(true)
+                        .create(true)
+                        .truncate(false)
+                        .open(&self.path)
+                        .await?,
+                );
+            }
+            if self.file.is_none() {
+                return Err(FileError::Closed);
+            }
+            Ok(())
+        }
+
+        /// Creates the backend by opening the file at the given path.
+        ///
+        /// # Errors
+        ///
+        /// If the file does not exist or could not be opened for reading and writing, an error
+        /// variant is returned.
+        pub async fn from_path<P>(path: P) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            Ok(Self::new(
+                tokio::fs::OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(&path)
+                    .await?,
+                path.as_ref().to_owned(),
+            ))
+        }
+
+        /// Creates the backend by opening the file at the given path with preconfigured options,
+        /// for when more control over the file is needed than the other constructors provide.
+        ///
+        /// The backend both reads and writes the file, so the options should normally enable
+        /// both.
+        ///
+        /// # Errors
+        ///
+        /// If the file could not be opened with the given options, an error variant is returned.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::backend::File;
+        /// use tokio::fs::OpenOptions;
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-open-options.json");
+        ///     let _ = std::fs::remove_file(&path);
+        ///
+        ///     let mut options = OpenOptions::new();
+        ///     options.read(true).write(true).create_new(true);
+        ///
+        ///     assert!(File::from_open_options(&path, &options).await.is_ok());
+        ///     let err = File::from_open_options(&path, &options).await.unwrap_err();
+        ///     assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        pub async fn from_open_options<P>(
+            path: P,
+            options: &tokio::fs::OpenOptions,
+        ) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            let mut backend = Self::new(options.open(&path).await?, path.as_ref().to_owned());
+            backend.options = Some(options.clone());
+            Ok(backend)
+        }
+
+        /// Creates the backend by opening the file at the given path, refusing to read files
+        /// larger than `max` bytes. The file is read incrementally, so reading aborts as soon as
+        /// the limit is exceeded, without buffering the whole file into memory.
+        ///
+        /// # Errors
+        ///
+        /// If the file does not exist or could not be opened for reading and writing, an error
+        /// variant is returned.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::backend::{Backend, File, FileError};
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-read-limit.json");
+        ///     std::fs::write(&path, "[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]")?;
+        ///
+        ///     let mut backend = File::with_read_limit(&path, 8).await?;
+        ///     assert!(matches!(backend.read().await, Err(FileError::TooLarge { limit: 8 })));
+        ///
+        ///     let mut backend = File::with_read_limit(&path, 1024).await?;
+        ///     assert_eq!(backend.read().await?, b"[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]");
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        pub async fn with_read_limit<P>(path: P, max: u64) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            let mut backend = Self::from_path(path).await?;
+            backend.read_limit = Some(max);
+            Ok(backend)
+        }
+
+        /// Creates the backend by opening a file at the given path. Creates the file if it
+        /// does not exist yet.
+        ///
+        /// # Errors
+        ///
+        /// If the file does not exist, but could not be created, or could not be opened for
+        /// reading and writing, an error variant is returned.
+        pub async fn from_path_or_create<P>(path: P) -> Result<(Self, bool), std::io::Error>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            let backend = Self::from_path(&path).await;
+            match backend {
+                Ok(self_) => Ok((self_, true)),
+                Err(err) => match err.kind() {
+                    std::io::ErrorKind::NotFound => Ok((
+                        Self::new(
+                            tokio::fs::OpenOptions::new()
+                                .read(true)
+                                .write(true)
+                                .create(true)
+                                .truncate(false)
+                                .open(&path)
+                                .await?,
+                            path.as_ref().to_owned(),
+                        ),
+                        false,
+                    )),
+                    _ => Err(err),
+                },
+            }
+        }
+    }
+
+    /// When [`File`](crate::backend::File) writes are flushed to disk, as set by
+    /// [`FileBuilder::sync`](crate::backend::FileBuilder::sync).
+    ///
+    /// More policies may be added in the future.
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum SyncPolicy {
+        /// Every write is flushed to disk before it completes.
+        Always,
+        /// Writes complete without waiting for the data to reach the disk.
+        Never,
+    }
+
+    /// A builder for [`File`](crate::backend::File) backends, combining options that the
+    /// constructors of `File` each offer separately.
+    ///
+    /// By default, the file must exist, is written in place, writes are flushed to disk, and
+    /// reads are not limited.
+    ///
+    /// There is no compression option: koit does not depend on a compression library, so files
+    /// hold the bytes as the format encodes them. To compress the data, do so in the format, for
+    /// example with a [`Custom`](crate::format::Custom) format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::backend::{Backend, FileBuilder, SyncPolicy};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-file-builder.json");
+    ///     let _ = std::fs::remove_file(&path);
+    ///
+    ///     let mut backend = FileBuilder::new()
+    ///         .atomic(true)
+    ///         .sync(SyncPolicy::Always)
+    ///         .read_limit(1024)
+    ///         .create(true)
+    ///         .open(&path)
+    ///         .await?;
+    ///
+    ///     backend.write(b"[1, 2, 3]".to_vec()).await?;
+    ///     assert_eq!(backend.read().await?, b"[1, 2, 3]");
+    ///     assert_eq!(std::fs::read(&path)?, b"[1, 2, 3]");
+    ///
+    ///     // No temporary files are left behind.
+    ///     let leftovers = std::fs::read_dir(std::env::temp_dir())?
+    ///         .filter_map(Result::ok)
+    ///         .filter(|entry| {
+    ///             let name = entry.file_name();
+    ///             name.to_string_lossy().starts_with("koit-doc-file-builder.json.")
+    ///         })
+    ///         .count();
+    ///     assert_eq!(leftovers, 0);
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+    #[derive(Debug, Clone)]
+    pub struct FileBuilder {
+        atomic: bool,
+        sync: SyncPolicy,
+        read_limit: Option<u64>,
+        create: bool,
+    }
+
+    impl Default for FileBuilder {
+        fn default() -> Self {
+            Self {
+                atomic: false,
+                sync: SyncPolicy::Always,
+                read_limit: None,
+                create: false,
+            }
+        }
+    }
+
+    impl FileBuilder {
+        /// Creates a builder with the default options.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Set whether writes replace the file atomically, as done by
+        /// [`AtomicFile`](crate::backend::AtomicFile): data is written to a uniquely named
+        /// temporary file next to the backing file, which is then renamed over it.
+        pub fn atomic(mut self, atomic: bool) -> Self {
+            self.atomic = atomic;
+            self
+        }
+
+        /// Set when writes are flushed to disk. See
+        /// [`File::with_sync`](crate::backend::File::with_sync).
+        pub fn sync(mut self, policy: SyncPolicy) -> Self {
+            self.sync = policy;
+            self
+        }
+
+        /// Refuse to read files larger than `max` bytes. See
+        /// [`File::with_read_limit`](crate::backend::File::with_read_limit).
+        pub fn read_limit(mut self, max: u64) -> Self {
+            self.read_limit = Some(max);
+            self
+        }
+
+        /// Set whether the file is created if it does not exist.
+        pub fn create(mut self, create: bool) -> Self {
+            self.create = create;
+            self
+        }
+
+        /// Creates the backend by opening the file at the given path with the configured
+        /// options.
+        ///
+        /// # Errors
+        ///
+        /// If the file does not exist and is not to be created, or could not be opened for
+        /// reading and writing, an error variant is returned.
+        pub async fn open<P>(&self, path: P) -> Result<File, std::io::Error>
+        where
+            P: AsRef<Path>,
+        {
+            let file = tokio::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(self.create)
+                .truncate(false)
+                .open(&path)
+                .await?;
+            let mut backend = File::new(file, path.as_ref().to_owned());
+            backend.atomic = self.atomic;
+            backend.sync = self.sync == SyncPolicy::Always;
+            backend.read_limit = self.read_limit;
+            Ok(backend)
+        }
+    }
+
+    #[async_trait]
+    impl Backend for File {
+        type Error = FileError;
+
+        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+            let read_limit = self.read_limit;
+            if let Some(pending) = self
+                .buffer
+                .as_ref()
+                .and_then(|buffer| buffer.pending.as_ref())
+            {
+                match read_limit {
+                    Some(limit) if pending.len() as u64 > limit => {
+                        return Err(FileError::TooLarge { limit })
+                    }
+                    _ => return Ok(pending.clone()),
+                }
+            }
+            if self.is_unopened() {
+                return Ok(Vec::new());
+            }
+            let file = self.handle()?;
+            let mut buffer = Vec::new();
+            file.seek(std::io::SeekFrom::Start(0)).await?;
+            match read_limit {
+                Some(limit) => {
+                    // Read one byte past the limit to detect whether it is exceeded.
+                    file.take(limit.saturating_add(1))
+                        .read_to_end(&mut buffer)
+                        .await?;
+                    if buffer.len() as u64 > limit {
+                        return Err(FileError::TooLarge { limit });
+                    }
+                }
+                None => {
+                    file.read_to_end(&mut buffer).await?;
+                }
+            }
+            Ok(buffer)
+        }
+
+        /// Checks the size of the file, or of a [buffered](crate::backend::File::buffered) write
+        /// that is held back. A file that is yet to be created does not exist.
+        async fn exists(&mut self) -> Result<bool, Self::Error> {
+            if let Some(pending) = self
+                .buffer
+                .as_ref()
+                .and_then(|buffer| buffer.pending.as_ref())
+            {
+                return Ok(!pending.is_empty());
+            }
+            let metadata = if self.is_unopened() {
+                match tokio::fs::metadata(&self.path).await {
+                    Ok(metadata) => metadata,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+                    Err(err) => return Err(err.into()),
+                }
+            } else {
+                self.handle()?.metadata().await?
+            };
+            Ok(metadata.len() > 0)
+        }
+
+        /// Checks that the file is open and its metadata can be queried, unless it is yet to be
+        /// created, and that a uniquely named scratch file can be created next to it. The backing
+        /// file is not read or written, and existing files are never overwritten.
+        async fn health_check(&mut self) -> Result<(), Self::Error> {
+            if !self.is_unopened() {
+                self.handle()?.metadata().await?;
+            }
+            let scratch_path = self.unique_temp_path(".health");
+            tokio::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&scratch_path)
+                .await?;
+            tokio::fs::remove_file(&scratch_path).await?;
+            Ok(())
+        }
+
+        /// Returns a reader over a clone of the held file handle, buffered by a
+        /// [`BufReader`](tokio::io::BufReader). The clone refers to the same open file as the
+        /// backend, even if the file was renamed or removed since, and shares its position: the
+        /// reader should be consumed before the backend is used again.
+        ///
+        /// If a [read limit](crate::backend::File::with_read_limit) is set, or a
+        /// [buffered](crate::backend::File::buffered) write is held back, `None` is returned.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use koit::{backend::{Backend, File}, format::{Format, Ndjson}};
+        ///
+        /// #[tokio::main]
+        /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let path = std::env::temp_dir().join("koit-doc-read-buffered.ndjson");
+        ///     std::fs::write(&path, "{\"cats\": 3}\n\n{\"yaks\": [1, 2]}\n")?;
+        ///     let mut backend = File::from_path(&path).await?;
+        ///
+        ///     // Decode one line at a time.
+        ///     let reader = backend.read_buffered().await?.unwrap();
+        ///     let buffered: Vec<serde_json::Value> = Ndjson.from_buf_read(reader).await?;
+        ///
+        ///     let whole: Vec<serde_json::Value> = Ndjson.from_bytes(backend.read().await?)?;
+        ///     assert_eq!(buffered.len(), 2);
+        ///     assert_eq!(buffered, whole);
+        ///
+        ///     std::fs::remove_file(&path)?;
+        ///     Ok(())
+        /// }
+        /// ```
+        async fn read_buffered(
+            &mut self,
+        ) -> Result<Option<Box<dyn tokio::io::AsyncBufRead + Send + Unpin>>, Self::Error> {
+            let pending = self
+                .buffer
+                .as_ref()
+                .is_some_and(|buffer| buffer.pending.is_some());
+            if self.read_limit.is_some() || pending || self.is_unopened() {
+                return Ok(None);
+            }
+
+            let file = self.handle()?;
+            file.seek(std::io::SeekFrom::Start(0)).await?;
+            let file = file.try_clone().await?;
+            Ok(Some(Box::new(tokio::io::BufReader::new(file))))
+        }
+
+        async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+            self.open_for_write().await?;
+            match self.buffer.as_mut() {
+                Some(buffer) => {
+                    buffer.buffered = buffer.buffered.saturating_add(data.len());
+                    buffer.pending = Some(data);
+                    if buffer.buffered >= buffer.capacity {
+                        self.flush().await?;
+                    }
+                    Ok(())
+                }
+                None => self.write_through(&data).await,
+            }
+        }
+
+        async fn write_chunked(
+            &mut self,
+            data: Vec<u8>,
+            chunk_size: usize,
+            progress: &mut (dyn FnMut(u64, u64) + Send),
+        ) -> Result<(), Self::Error> {
+            if self.buffer.is_some() || self.atomic {
+                let total = data.len() as u64;
+                self.write(data).await?;
+                progress(total, total);
+                return Ok(());
+            }
+            self.open_for_write().await?;
+            self.write_through_chunked(&data, chunk_size, progress)
+                .await
+        }
+
+        /// Buffered and atomic backends collect the chunks and write them at once. Otherwise,
+        /// chunks are written to a temporary file next to the backing file as they are produced,
+        /// which is renamed into place once all are written. Either way, the backing file is left
+        /// as it was if a chunk is an error.
+        async fn write_streamed(
+            &mut self,
+            chunks: &mut (dyn Iterator<Item = std::io::Result<Vec<u8>>> + Send),
+        ) -> Result<(), Self::Error> {
+            if self.buffer.is_some() || self.atomic {
+                return match super::collect_chunks(chunks) {
+                    Some(data) => self.write(data).await,
+                    None => Ok(()),
+                };
+            }
+            if self.file.is_none() && !self.is_unopened() {
+                return Err(FileError::Closed);
+            }
+            let temp_path = self.unique_temp_path(".tmp");
+            self.stream_via(&temp_path, chunks).await
+        }
+    }
+}
+
+#[cfg(feature = "file-backend")]
+mod journaling {
+    use std::convert::TryFrom;
+    use std::path::{Path, PathBuf};
+
+    use async_trait::async_trait;
+    use tokio::io::AsyncWriteExt;
+
+    use super::file::with_suffix;
+    use super::{Backend, File, FileError};
+
+    /// A file-backed backend keeping a journal of the save in progress.
+    ///
+    /// Before the backing file is overwritten, its old contents and the new contents are written
+    /// to a journal next to it, at the backing file's path with `.journal` appended. The journal
+    /// is removed once the backing file is successfully written. If a save is interrupted, the
+    /// journal remains, and [`recover`](crate::backend::JournalingFile::recover) completes the
+    /// save on the next start.
+    ///
+    /// The journal consists of the old contents followed by the new contents, each prefixed by
+    /// their length in bytes as a little-endian `u64`.
+    ///
+    /// Note: this requires its futures to be executed on the Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// Recovering from a save that was interrupted after writing the journal:
+    ///
+    /// ```
+    /// use koit::backend::{Backend, JournalingFile};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-journaling.json");
+    ///     let journal_path = std::env::temp_dir().join("koit-doc-journaling.json.journal");
+    ///     std::fs::write(&path, "[1]")?;
+    ///
+    ///     let mut journal = Vec::new();
+    ///     for contents in &[&b"[1]"[..], &b"[1, 2]"[..]] {
+    ///         journal.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+    ///         journal.extend_from_slice(contents);
+    ///     }
+    ///     std::fs::write(&journal_path, journal)?;
+    ///
+    ///     let mut backend = JournalingFile::from_path(&path).await?;
+    ///     assert!(backend.recover().await?);
+    ///     assert_eq!(backend.read().await?, b"[1, 2]");
+    ///     assert!(!journal_path.exists());
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+    #[derive(Debug)]
+    pub struct JournalingFile {
+        file: File,
+        journal_path: PathBuf,
+    }
+
+    impl JournalingFile {
+        /// Creates the backend by opening the file at the given path.
+        ///
+        /// This does not recover interrupted saves; call
+        /// [`recover`](crate::backend::JournalingFile::recover) for that.
+        ///
+        /// # Errors
+        ///
+        /// If the file does not exist or could not be opened for reading and writing, an error
+        /// variant is returned.
+        pub async fn from_path<P>(path: P) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<Path>,
+        {
+            Ok(Self {
+                file: File::from_path(&path).await?,
+                journal_path: with_suffix(path.as_ref(), ".journal"),
+            })
+        }
+
+        /// Complete an interrupted save, if there is one, by writing the new contents recorded in
+        /// the journal to the backing file. Returns whether a save was recovered.
+        ///
+        /// A journal that was itself only partially written is discarded: the save was
+        /// interrupted before the backing file was touched.
+        ///
+        /// # Errors
+        ///
+        /// If the journal could not be read, or the backing file could not be written, an error
+        /// variant is returned.
+        pub async fn recover(&mut self) -> Result<bool, FileError> {
+            let journal = match tokio::fs::read(&self.journal_path).await {
+                Ok(journal) => journal,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+                Err(err) => return Err(err.into()),
+            };
+
+            let recovered = match parse_journal(&journal) {
+                Some((_old, new)) => {
+                    self.file.write(new.to_vec()).await?;
+                    true
+                }
+                None => false,
+            };
+            tokio::fs::remove_file(&self.journal_path).await?;
+            Ok(recovered)
+        }
+    }
+
+    /// Split the journal into the old and new contents, or `None` if it is incomplete.
+    fn parse_journal(journal: &[u8]) -> Option<(&[u8], &[u8])> {
+        fn entry(journal: &[u8]) -> Option<(&[u8], &[u8])> {
+            if journal.len() < 8 {
+                return None;
+            }
+            let (len, rest) = journal.split_at(8);
+            let mut len_bytes = [0; 8];
+            len_bytes.copy_from_slice(len);
+            let len = usize::try_from(u64::from_le_bytes(len_bytes)).ok()?;
+            if rest.len() < len {
+                return None;
+            }
+            Some(rest.split_at(len))
+        }
+
+        let (old, rest) = entry(journal)?;
+        let (new, rest) = entry(rest)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Some((old, new))
+    }
+
+    #[async_trait]
+    impl Backend for JournalingFile {
+        type Error = FileError;
+
+        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+            self.file.read().await
+        }
+
+        async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+            let old = self.file.read().await?;
+
+            let mut journal = tokio::fs::File::create(&self.journal_path).await?;
+            for contents in &[&old, &data] {
+                journal
+                    .write_all(&(contents.len() as u64).to_le_bytes())
+                    .await?;
+                journal.write_all(contents).await?;
+            }
+            journal.sync_all().await?;
+            drop(journal);
+
+            self.file.write(data).await?;
+            tokio::fs::remove_file(&self.journal_path).await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "file-backend")]
+mod atomic {
+    use std::path::{Path, PathBuf};
+
+    use async_trait::async_trait;
+
+    use super::file::with_unique_suffix;
+    use super::{Backend, File, FileError};
+
+    /// A file-backed backend replacing the file atomically on every write.
+    ///
+    /// Data is written to a temporary file, which is then renamed over the backing file. Readers
+    /// of the file, and the backend itself after a crash, see either the old or the new contents,
+    /// never a mix. By default, the temporary file is created next to the backing file, at its
+    /// path with `.tmp` appended.
+    ///
+    /// Note: this requires its futures to be executed on the Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::backend::{AtomicFile, Backend};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-atomic.json");
+    ///     let temp_dir = std::env::temp_dir().join("koit-doc-atomic-temp");
+    ///     std::fs::write(&path, "[]")?;
+    ///     std::fs::create_dir_all(&temp_dir)?;
+    ///
+    ///     let mut backend = AtomicFile::with_temp_dir(&path, &temp_dir).await?;
+    ///     backend.write(b"[1, 2]".to_vec()).await?;
+    ///     assert_eq!(backend.read().await?, b"[1, 2]");
+    ///     assert_eq!(std::fs::read(&path)?, b"[1, 2]");
+    ///     assert_eq!(std::fs::read_dir(&temp_dir)?.count(), 0);
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     std::fs::remove_dir(&temp_dir)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+    #[derive(Debug)]
+    pub struct AtomicFile {
+        file: File,
+        /// The path temporary files are named after, with a unique suffix appended.
+        temp_base: PathBuf,
+    }
+
+    impl AtomicFile {
+        /// Creates the backend by opening the file at the given path.
+        ///
+        /// # Errors
+        ///
+        /// If the file does not exist or could not be opened for reading and writing, an error
+        /// variant is returned.
+        pub async fn from_path<P>(path: P) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<Path>,
+        {
+            let file = File::from_path(&path).await?;
+            Ok(Self {
+                temp_base: file.path().to_owned(),
+                file,
+            })
+        }
+
+        /// Creates the backend by opening the file at the given path, creating temporary files
+        /// in the given directory.
+        ///
+        /// Renaming is only atomic within a filesystem, so the directory must be on the same
+        /// filesystem as the file. This is checked on Unix platforms only.
+        ///
+        /// # Errors
+        ///
+        /// If the file does not exist or could not be opened for reading and writing, or the
+        /// directory could not be inspected or is on another filesystem, an error variant is
+        /// returned.
+        pub async fn with_temp_dir<P, T>(path: P, temp_dir: T) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<Path>,
+            T: AsRef<Path>,
+        {
+            let path = path.as_ref();
+            let temp_dir = temp_dir.as_ref();
+            check_same_filesystem(path, temp_dir).await?;
+
+            Ok(Self {
+                file: File::from_path(path).await?,
+                temp_base: temp_dir.join(path.file_name().unwrap_or_default()),
+            })
+        }
+    }
+
+    #[cfg(unix)]
+    async fn check_same_filesystem(path: &Path, dir: &Path) -> Result<(), std::io::Error> {
+        use std::os::unix::fs::MetadataExt;
+
+        let file_device = tokio::fs::metadata(path).await?.dev();
+        let dir_metadata = tokio::fs::metadata(dir).await?;
+        if !dir_metadata.is_dir() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the temporary directory is not a directory",
+            ));
+        }
+        if dir_metadata.dev() != file_device {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the temporary directory is on another filesystem than the file",
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    async fn check_same_filesystem(_path: &Path, dir: &Path) -> Result<(), std::io::Error> {
+        if !tokio::fs::metadata(dir).await?.is_dir() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the temporary directory is not a directory",
+            ));
+        }
+        Ok(())
+    }
+
+    #[async_trait]
+    impl Backend for AtomicFile {
+        type Error = FileError;
+
+        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+            self.file.read().await
+        }
+
+        async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+            let temp_path = with_unique_suffix(&self.temp_base, ".tmp");
+            self.file.write_via(&temp_path, &data).await
+        }
+    }
+}
+
+#[cfg(feature = "file-backend")]
+mod snapshot_dir {
+    use std::path::{Path, PathBuf};
+    use std::time::UNIX_EPOCH;
+
+    use async_trait::async_trait;
+
+    use super::Backend;
+    use crate::clock::{Clock, SystemClock};
+
+    /// A directory-backed backend keeping every save as a separate snapshot.
+    ///
+    /// Each write creates a new file `<unix-millis>.blob` in the directory, named after the time
+    /// of the write in milliseconds since the Unix epoch. Reads return the newest snapshot. Older
+    /// snapshots are kept until removed by the user, and can be brought back with
+    /// [`restore`](crate::backend::SnapshotDir::restore).
+    ///
+    /// Timestamps are strictly increasing: writes happening within the same millisecond are
+    /// given successive timestamps.
+    ///
+    /// Note: this requires its futures to be executed on the Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::SnapshotDir};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let dir = std::env::temp_dir().join("koit-doc-snapshot-dir");
+    ///     let backend = SnapshotDir::from_path(&dir).await?;
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), backend);
+    ///
+    ///     for n in 1..=3 {
+    ///         db.write(|numbers| numbers.push(n)).await;
+    ///         db.save().await?;
+    ///     }
+    ///
+    ///     let (_, mut backend) = db.into_parts();
+    ///     let snapshots = backend.list_snapshots().await?;
+    ///     assert_eq!(snapshots.len(), 3);
+    ///     assert_eq!(std::fs::read_dir(&dir)?.count(), 3);
+    ///
+    ///     backend.restore(snapshots[0]).await?;
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), backend);
+    ///     db.reload().await?;
+    ///     db.read(|numbers| assert_eq!(numbers, &[1])).await;
+    ///
+    ///     std::fs::remove_dir_all(&dir)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+    #[derive(Debug)]
+    pub struct SnapshotDir {
+        dir: PathBuf,
+        last_timestamp: u64,
+        clock: Box<dyn Clock>,
+    }
+
+    impl SnapshotDir {
+        /// Creates the backend on the directory at the given path, creating the directory if it
+        /// does not exist.
+        ///
+        /// # Errors
+        ///
+        /// If the directory could not be created or listed, an error variant is returned.
+        pub async fn from_path<P>(path: P) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<Path>,
+        {
+            Self::with_boxed_clock(path.as_ref(), Box::new(SystemClock)).await
+        }
+
+        /// Same as [`from_path`](crate::backend::SnapshotDir::from_path), except snapshots are
+        /// timestamped using the given clock.
+        ///
+        /// # Errors
+        ///
+        /// If the directory could not be created or listed, an error variant is returned.
+        #[cfg(feature = "testing")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+        pub async fn with_clock<P, C>(path: P, clock: C) -> Result<Self, std::io::Error>
+        where
+            P: AsRef<Path>,
+            C: Clock + 'static,
+        {
+            Self::with_boxed_clock(path.as_ref(), Box::new(clock)).await
+        }
+
+        async fn with_boxed_clock(
+            path: &Path,
+            clock: Box<dyn Clock>,
+        ) -> Result<Self, std::io::Error> {
+            let dir = path.to_owned();
+            tokio::fs::create_dir_all(&dir).await?;
+            let mut backend = Self {
+                dir,
+                last_timestamp: 0,
+                clock,
+            };
+            backend.last_timestamp = backend.list_snapshots().await?.pop().unwrap_or(0);
+            Ok(backend)
+        }
+
+        /// Returns the timestamps of all snapshots in the directory, oldest first.
+        ///
+        /// # Errors
+        ///
+        /// If the directory could not be listed, an error variant is returned.
+        pub async fn list_snapshots(&self) -> Result<Vec<u64>, std::io::Error> {
+            let mut timestamps = Vec::new();
+            let mut entries = tokio::fs::read_dir(&self.dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension() == Some("blob".as_ref()) {
+                    if let Some(timestamp) = path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .and_then(|stem| stem.parse().ok())
+                    {
+                        timestamps.push(timestamp);
+                    }
+                }
+            }
+            timestamps.sort_unstable();
+            Ok(timestamps)
+        }
+
+        /// Make the snapshot with the given timestamp the newest, by writing a copy of it as a new
+        /// snapshot. Subsequent reads return its contents, and the snapshots in between are kept.
+        ///
+        /// # Errors
+        ///
+        /// If there is no snapshot with the given timestamp, or the copy failed to be written, an
+        /// error variant is returned.
+        pub async fn restore(&mut self, timestamp: u64) -> Result<(), std::io::Error> {
+            let data = tokio::fs::read(self.snapshot_path(timestamp)).await?;
+            self.write(data).await
+        }
+
+        fn snapshot_path(&self, timestamp: u64) -> PathBuf {
+            self.dir.join(format!("{}.blob", timestamp))
+        }
+
+        /// Returns the timestamp for a new snapshot.
+        fn next_timestamp(&mut self) -> u64 {
+            let now = self
+                .clock
+                .now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as u64);
+            self.last_timestamp = now.max(self.last_timestamp + 1);
+            self.last_timestamp
+        }
+    }
+
+    #[async_trait]
+    impl Backend for SnapshotDir {
+        type Error = std::io::Error;
+
+        /// Read the newest snapshot. If there are no snapshots yet, no bytes are returned.
+        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+            match self.list_snapshots().await?.pop() {
+                Some(timestamp) => tokio::fs::read(self.snapshot_path(timestamp)).await,
+                None => Ok(Vec::new()),
+            }
+        }
+
+        async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+            let timestamp = self.next_timestamp();
+            crate::write_synced(&self.snapshot_path(timestamp), &data).await
+        }
+    }
+}
+
+#[cfg(feature = "stream-backend")]
+mod stream {
+    use std::convert::TryFrom;
+
+    use async_trait::async_trait;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+    use super::Backend;
+
+    /// A backend over any seekable asynchronous stream, such as a section of a larger file or a
+    /// custom device.
+    ///
+    /// Streams cannot be truncated generically, so data is written over the start of the stream,
+    /// prefixed by its length in bytes as a little-endian `u64`. Reads return exactly that many
+    /// bytes, ignoring whatever remains of longer data written before. An empty stream holds no
+    /// data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use koit::backend::{Backend, Stream};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut backend = Stream::new(Cursor::new(Vec::new()));
+    ///     assert_eq!(backend.read().await?, b"");
+    ///
+    ///     backend.write(b"[1, 2, 3]".to_vec()).await?;
+    ///     backend.write(b"[1]".to_vec()).await?;
+    ///     assert_eq!(backend.read().await?, b"[1]");
+    ///
+    ///     let stream = backend.into_inner().into_inner();
+    ///     assert_eq!(&stream[..8], &3u64.to_le_bytes());
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// A corrupt length fails the read, without allocating that many bytes:
+    ///
+    /// ```
+    /// use std::io::{Cursor, ErrorKind};
+    /// use koit::backend::{Backend, Stream};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut stream = u64::MAX.to_le_bytes().to_vec();
+    ///     stream.extend_from_slice(b"[1]");
+    ///
+    ///     let mut backend = Stream::new(Cursor::new(stream));
+    ///     let err = backend.read().await.unwrap_err();
+    ///     assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream-backend")))]
+    #[derive(Debug)]
+    pub struct Stream<T> {
+        inner: T,
+    }
+
+    impl<T> Stream<T> {
+        /// Creates the backend over the given stream.
+        pub fn new(inner: T) -> Self {
+            Self { inner }
+        }
+
+        /// Returns a reference to the stream.
+        pub fn inner(&self) -> &T {
+            &self.inner
+        }
+
+        /// Consume the backend and return the stream.
+        pub fn into_inner(self) -> T {
+            self.inner
+        }
+    }
+
+    #[async_trait]
+    impl<T> Backend for Stream<T>
+    where
+        T: AsyncRead + AsyncWrite + AsyncSeek + Unpin + Send,
+    {
+        type Error = std::io::Error;
+
+        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+            self.inner.seek(std::io::SeekFrom::Start(0)).await?;
+            let mut len_bytes = [0; 8];
+            match self.inner.read_exact(&mut len_bytes).await {
+                Ok(_) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return Ok(Vec::new())
+                }
+                Err(err) => return Err(err),
+            }
+            // The length is not trusted to size the buffer up front, as a corrupt length could
+            // exhaust memory; the buffer only grows with the bytes actually read.
+            let len = u64::from_le_bytes(len_bytes);
+            let mut data = Vec::new();
+            (&mut self.inner).take(len).read_to_end(&mut data).await?;
+            if u64::try_from(data.len()) != Ok(len) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "the stream is shorter than its stored length",
+                ));
+            }
+            Ok(data)
+        }
+
+        async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+            self.inner.seek(std::io::SeekFrom::Start(0)).await?;
+            self.inner
+                .write_all(&(data.len() as u64).to_le_bytes())
+                .await?;
+            self.inner.write_all(&data).await?;
+            self.inner.flush().await
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "unix-socket-backend"))]
+mod unix_socket {
+    use std::convert::TryFrom;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::{Path, PathBuf};
+
+    use async_trait::async_trait;
+
+    use super::Backend;
+
+    /// The request byte asking the peer for its data.
+    const READ: u8 = b'R';
+    /// The request byte handing the peer new data.
+    const WRITE: u8 = b'W';
+
+    /// A backend exchanging data with a peer process over a Unix domain socket, for example a
+    /// companion process keeping the data.
+    ///
+    /// Every read and write connects to the socket, and performs one exchange. The peer must
+    /// implement the matching protocol, in which blobs are prefixed by their length in bytes as
+    /// a little-endian `u64`:
+    ///
+    /// - The backend sends a request byte, `R` to read or `W` to write, followed by a blob: empty
+    ///   for reads, the data to store for writes.
+    /// - The peer responds with a blob: the stored data for reads, and an empty blob to
+    ///   acknowledge writes.
+    ///
+    /// Socket I/O is blocking, so it is done on Tokio's blocking thread pool.
+    ///
+    /// Note: this requires its futures to be executed on the Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Write};
+    /// use std::os::unix::net::{UnixListener, UnixStream};
+    /// use koit::backend::{Backend, UnixSocket};
+    ///
+    /// fn read_blob(stream: &mut UnixStream) -> std::io::Result<Vec<u8>> {
+    ///     let mut len = [0; 8];
+    ///     stream.read_exact(&mut len)?;
+    ///     let mut blob = Vec::new();
+    ///     stream.take(u64::from_le_bytes(len)).read_to_end(&mut blob)?;
+    ///     Ok(blob)
+    /// }
+    ///
+    /// fn write_blob(stream: &mut UnixStream, blob: &[u8]) -> std::io::Result<()> {
+    ///     stream.write_all(&(blob.len() as u64).to_le_bytes())?;
+    ///     stream.write_all(blob)
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-unix-socket.sock");
+    ///     let _ = std::fs::remove_file(&path);
+    ///     let listener = UnixListener::bind(&path)?;
+    ///
+    ///     // A peer keeping the last blob written, and handing it back on reads.
+    ///     let peer = std::thread::spawn(move || -> std::io::Result<()> {
+    ///         let mut stored = Vec::new();
+    ///         for _ in 0..2 {
+    ///             let (mut stream, _) = listener.accept()?;
+    ///             let mut request = [0];
+    ///             stream.read_exact(&mut request)?;
+    ///             let blob = read_blob(&mut stream)?;
+    ///             match &request {
+    ///                 b"W" => {
+    ///                     stored = blob;
+    ///                     write_blob(&mut stream, b"")?;
+    ///                 }
+    ///                 _ => write_blob(&mut stream, &stored)?,
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     });
+    ///
+    ///     let mut backend = UnixSocket::new(&path);
+    ///     backend.write(b"[1, 2, 3]".to_vec()).await?;
+    ///     assert_eq!(backend.read().await?, b"[1, 2, 3]");
+    ///
+    ///     peer.join().unwrap()?;
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix-socket-backend"))))]
+    #[derive(Debug, Clone)]
+    pub struct UnixSocket {
+        path: PathBuf,
+    }
+
+    impl UnixSocket {
+        /// Creates the backend for the socket at the given path. The socket is not connected
+        /// to until the first read or write.
+        pub fn new<P>(path: P) -> Self
+        where
+            P: AsRef<Path>,
+        {
+            Self {
+                path: path.as_ref().to_owned(),
+            }
+        }
+
+        /// Returns the path of the socket.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// Connect to the socket, send the request and its blob, and return the response blob.
+        async fn exchange(&self, request: u8, blob: Vec<u8>) -> Result<Vec<u8>, std::io::Error> {
+            let path = self.path.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut stream = UnixStream::connect(path)?;
+                stream.write_all(&[request])?;
+                stream.write_all(&(blob.len() as u64).to_le_bytes())?;
+                stream.write_all(&blob)?;
+                stream.flush()?;
+
+                let mut len_bytes = [0; 8];
+                stream.read_exact(&mut len_bytes)?;
+                // The peer's length is not trusted to size the buffer up front; the buffer only
+                // grows with the bytes actually received.
+                let len = u64::from_le_bytes(len_bytes);
+                let mut response = Vec::new();
+                (&mut stream).take(len).read_to_end(&mut response)?;
+                if u64::try_from(response.len()) != Ok(len) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "the peer closed the connection before sending its whole response",
+                    ));
+                }
+                Ok(response)
+            })
+            .await
+            .map_err(std::io::Error::other)?
+        }
+    }
+
+    #[async_trait]
+    impl Backend for UnixSocket {
+        type Error = std::io::Error;
+
+        async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+            self.exchange(READ, Vec::new()).await
+        }
+
+        async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+            let response = self.exchange(WRITE, data).await?;
+            if !response.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "the peer did not acknowledge the write with an empty blob",
+                ));
+            }
             Ok(())
         }
     }
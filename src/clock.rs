@@ -0,0 +1,88 @@
+use std::time::SystemTime;
+
+/// A source of the current time, for time-dependent features such as
+/// [snapshot timestamps](crate::backend::SnapshotDir).
+///
+/// Outside of tests, the [system clock](crate::SystemClock) is used.
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// The system's real-time clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+#[cfg(feature = "testing")]
+pub use self::mock::MockClock;
+
+#[cfg(feature = "testing")]
+mod mock {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    use super::Clock;
+
+    /// A [`Clock`](crate::Clock) that only moves when told to, for deterministic tests.
+    ///
+    /// Clones share the same time, so a test can keep a clone to advance the clock after
+    /// handing it to the code under test.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use koit::{MockClock, backend::{Backend, SnapshotDir}};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let dir = std::env::temp_dir().join("koit-doc-mock-clock");
+    ///     let clock = MockClock::new(UNIX_EPOCH + Duration::from_millis(1000));
+    ///     let mut backend = SnapshotDir::with_clock(&dir, clock.clone()).await?;
+    ///
+    ///     backend.write(b"[1]".to_vec()).await?;
+    ///     clock.advance(Duration::from_millis(500));
+    ///     backend.write(b"[1, 2]".to_vec()).await?;
+    ///     assert_eq!(backend.list_snapshots().await?, vec![1000, 1500]);
+    ///
+    ///     std::fs::remove_dir_all(&dir)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+    #[derive(Debug, Clone)]
+    pub struct MockClock {
+        now: Arc<Mutex<SystemTime>>,
+    }
+
+    impl MockClock {
+        /// Create the clock, starting at the given time.
+        pub fn new(now: SystemTime) -> Self {
+            Self {
+                now: Arc::new(Mutex::new(now)),
+            }
+        }
+
+        /// Set the current time.
+        pub fn set(&self, now: SystemTime) {
+            *self.now.lock().unwrap() = now;
+        }
+
+        /// Move the current time forward by the given duration.
+        pub fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> SystemTime {
+            *self.now.lock().unwrap()
+        }
+    }
+}
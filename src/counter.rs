@@ -0,0 +1,131 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::sync::Mutex;
+
+use crate::{Backend, Format, KoitError};
+
+/// A database holding a single `u64` counter.
+///
+/// Unlike a [`Database<u64, _, _>`](crate::Database), the counter is kept in an atomic
+/// integer, so reading and updating it never takes a lock. Only saving and reloading lock the
+/// backend.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use koit::{CounterDatabase, format::Json, backend::Memory};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let counter: Arc<CounterDatabase<_, Json>> =
+///         Arc::new(CounterDatabase::from_parts(0, Memory::default()));
+///
+///     let tasks: Vec<_> = (0..8)
+///         .map(|_| {
+///             let counter = counter.clone();
+///             tokio::spawn(async move {
+///                 for _ in 0..1000 {
+///                     counter.incr(1);
+///                 }
+///             })
+///         })
+///         .collect();
+///     for task in tasks {
+///         task.await.unwrap();
+///     }
+///     counter.save().await.unwrap();
+///
+///     counter.set(0);
+///     counter.reload().await.unwrap();
+///     assert_eq!(counter.get(), 8000);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CounterDatabase<B, F> {
+    value: AtomicU64,
+    backend: Mutex<B>,
+    format: F,
+}
+
+impl<B, F> CounterDatabase<B, F>
+where
+    B: Backend,
+    F: Format<u64>,
+{
+    /// Create a counter database from its constituents.
+    pub fn from_parts(value: u64, backend: B) -> Self
+    where
+        F: Default,
+    {
+        Self::from_parts_with_format(value, backend, F::default())
+    }
+
+    /// Create a counter database from its constituents, using the given format instance.
+    pub fn from_parts_with_format(value: u64, backend: B, format: F) -> Self {
+        Self {
+            value: AtomicU64::new(value),
+            backend: Mutex::new(backend),
+            format,
+        }
+    }
+
+    /// Returns the current value of the counter.
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::SeqCst)
+    }
+
+    /// Add `n` to the counter, wrapping around on overflow, and return the new value.
+    pub fn incr(&self, n: u64) -> u64 {
+        self.value.fetch_add(n, Ordering::SeqCst).wrapping_add(n)
+    }
+
+    /// Set the counter to the given value, returning the old value.
+    pub fn set(&self, value: u64) -> u64 {
+        self.value.swap(value, Ordering::SeqCst)
+    }
+
+    /// Flush the current value of the counter to the backend. Updates made while the save is in
+    /// progress are picked up by the next save.
+    ///
+    /// # Errors
+    ///
+    /// - If the value failed to be encoded by the format, an error variant is returned.
+    /// - If the bytes failed to be written to the backend, an error variant is returned.
+    pub async fn save(&self) -> Result<(), KoitError> {
+        let mut backend = self.backend.lock().await;
+        let bytes = self
+            .format
+            .to_bytes(&self.get())
+            .map_err(|err| KoitError::ToFormat(err.into()))?;
+        backend
+            .write(bytes)
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))
+    }
+
+    /// Update the counter with the value from the backend, returning the old value.
+    ///
+    /// # Errors
+    ///
+    /// - If the bytes failed to be read by the backend, an error variant is returned.
+    /// - If the bytes from the backend failed to be decoded by the format, an error variant is
+    ///   returned.
+    pub async fn reload(&self) -> Result<u64, KoitError> {
+        let mut backend = self.backend.lock().await;
+        let bytes = backend
+            .read()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+        let value = self
+            .format
+            .from_bytes(bytes)
+            .map_err(|err| KoitError::FromFormat(err.into()))?;
+        Ok(self.set(value))
+    }
+
+    /// Consume the database and return its value and backend.
+    pub fn into_parts(self) -> (u64, B) {
+        (self.value.into_inner(), self.backend.into_inner())
+    }
+}
@@ -5,6 +5,7 @@ use thiserror::Error;
 /// The concrete source error types are the associated errors types
 /// [`Format::Error`](crate::format::Format::Error) and [`Backend::Error`](crate::backend::Backend::Error).
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum KoitError {
     /// Data failed to be encoded by the formatter.
     #[error("the database failed to serialize")]
@@ -21,4 +22,83 @@ pub enum KoitError {
     /// The backend failed to be created.
     #[error("failed to create backend")]
     BackendCreation(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// Saved data did not decode to the data in the database.
+    #[error("the saved data did not match the data in the database")]
+    Validation,
+    /// None of the candidates could be loaded. Contains the error for each candidate.
+    #[error("none of the candidates could be loaded")]
+    NoValidCandidate(Vec<KoitError>),
+    /// The operation did not complete in time.
+    #[error("the operation timed out")]
+    Timeout,
+    /// A patch failed to be applied to the data.
+    #[error("the patch failed to apply")]
+    Patch(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// The database was [finalized](crate::Database::finalize).
+    #[error("the database is finalized")]
+    Finalized,
+    /// The backend held no bytes, where data was expected. This is distinct from
+    /// [`FromFormat`](crate::KoitError::FromFormat), as there was nothing to decode; for
+    /// example, the file exists but is empty.
+    #[error("the backend is empty")]
+    EmptyBackend,
+}
+
+/// Backend errors keep the [`ErrorKind`](std::io::ErrorKind) of the I/O error that caused them,
+/// if any. Format and validation errors become
+/// [`InvalidData`](std::io::ErrorKind::InvalidData).
+///
+/// # Examples
+///
+/// ```
+/// use koit::{Database, format::Json, backend::Memory};
+///
+/// fn load() -> std::io::Result<Vec<u32>> {
+///     futures::executor::block_on(async {
+///         let db: Database<Vec<u32>, _, Json> =
+///             Database::from_parts(Vec::new(), Memory::from("not json"));
+///         db.reload().await?;
+///         Ok(db.read(Vec::clone).await)
+///     })
+/// }
+///
+/// assert_eq!(load().unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+/// ```
+impl From<KoitError> for std::io::Error {
+    fn from(err: KoitError) -> Self {
+        use std::io::ErrorKind;
+
+        let kind = match &err {
+            KoitError::ToFormat(_)
+            | KoitError::FromFormat(_)
+            | KoitError::Validation
+            | KoitError::EmptyBackend => ErrorKind::InvalidData,
+            KoitError::BackendRead(source)
+            | KoitError::BackendWrite(source)
+            | KoitError::BackendCreation(source) => io_kind(&**source).unwrap_or(ErrorKind::Other),
+            KoitError::Timeout => ErrorKind::TimedOut,
+            KoitError::Patch(_) => ErrorKind::InvalidInput,
+            KoitError::NoValidCandidate(_) | KoitError::Finalized => ErrorKind::Other,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
+/// Returns the kind of the first I/O error in the chain of sources starting at the error.
+fn io_kind(err: &(dyn std::error::Error + 'static)) -> Option<std::io::ErrorKind> {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            return Some(err.kind());
+        }
+        // The I/O variant is transparent, so it does not show up as a source.
+        #[cfg(feature = "file-backend")]
+        {
+            if let Some(crate::backend::FileError::Io(err)) = err.downcast_ref() {
+                return Some(err.kind());
+            }
+        }
+        current = err.source();
+    }
+    None
 }
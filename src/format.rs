@@ -1,9 +1,14 @@
 //! Formats handle transforming structured data to and from bytes for persisting.
 
+use async_trait::async_trait;
+
 /// Trait implementable by format providers.
 ///
-/// By implementing this trait, a type becomes a marker for the specified format.
-/// That type can then be used for formatting (without instantiating an object of that type).
+/// Most formats are unit structs marking the specified format, such as
+/// [`Json`](crate::format::Json). Formats can also carry configuration or state, in which case the
+/// database is constructed with an instance of the format (see
+/// [`Database::from_parts_with_format`](crate::Database::from_parts_with_format)).
+#[async_trait]
 pub trait Format<T>: Sized {
     type Error: std::error::Error + Send + Sync + 'static;
 
@@ -12,42 +17,523 @@ pub trait Format<T>: Sized {
     /// # Errors
     ///
     /// If the data failed to be encoded by the format, an error variant is returned.
-    fn to_bytes(value: &T) -> Result<Vec<u8>, Self::Error>;
+    fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error>;
 
     /// Convert bytes to data.
     ///
     /// # Errors
     ///
     /// If the bytes failed to be decoded by the format, an error variant is returned.
-    fn from_bytes(data: Vec<u8>) -> Result<T, Self::Error>;
+    #[allow(clippy::wrong_self_convention)]
+    fn from_bytes(&self, data: Vec<u8>) -> Result<T, Self::Error>;
+
+    /// Convert data to a stable, canonical encoding: equal data always results in equal bytes.
+    /// This is useful for content-addressing and deduplication.
+    ///
+    /// The default implementation is [`to_bytes`](crate::format::Format::to_bytes), which is
+    /// canonical for formats without unordered constructs.
+    ///
+    /// # Errors
+    ///
+    /// If the data failed to be encoded by the format, an error variant is returned.
+    fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+        self.to_bytes(value)
+    }
+
+    /// Convert bytes from an asynchronous buffered reader to data. The database decodes through
+    /// this when the backend supports [buffered
+    /// reading](crate::backend::Backend::read_buffered).
+    ///
+    /// The default implementation reads all bytes into memory and calls
+    /// [`from_bytes`](crate::format::Format::from_bytes). Formats that can decode incrementally,
+    /// such as [`Ndjson`](crate::format::Ndjson), override it.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes failed to be read or decoded, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::format::{Format, Json};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let bytes = b"[1, 2, 3]";
+    ///     let buffered: Vec<u32> = Json.from_buf_read(&bytes[..]).await.unwrap();
+    ///     let whole: Vec<u32> = Json.from_bytes(bytes.to_vec()).unwrap();
+    ///     assert_eq!(buffered, whole);
+    /// });
+    /// ```
+    #[allow(clippy::wrong_self_convention)]
+    async fn from_buf_read<R>(&self, mut reader: R) -> Result<T, ReadError<Self::Error>>
+    where
+        R: tokio::io::AsyncBufRead + Send + Unpin,
+    {
+        let mut data = Vec::new();
+        read_until(&mut reader, None, &mut data)
+            .await
+            .map_err(ReadError::Io)?;
+        self.from_bytes(data).map_err(ReadError::Format)
+    }
+}
+
+/// Read bytes from the reader into `buf` up to and including `delimiter`, or up to the end of the
+/// reader if there is no delimiter. Returns the number of bytes read, which is 0 at the end of the
+/// reader.
+async fn read_until<R>(
+    reader: &mut R,
+    delimiter: Option<u8>,
+    buf: &mut Vec<u8>,
+) -> std::io::Result<usize>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let mut read = 0;
+    std::future::poll_fn(|cx| loop {
+        let mut reader = std::pin::Pin::new(&mut *reader);
+        let available = std::task::ready!(reader.as_mut().poll_fill_buf(cx))?;
+        let (used, done) = match delimiter
+            .and_then(|delimiter| available.iter().position(|&byte| byte == delimiter))
+        {
+            Some(index) => (index + 1, true),
+            None => (available.len(), available.is_empty()),
+        };
+        buf.extend_from_slice(&available[..used]);
+        reader.consume(used);
+        read += used;
+        if done {
+            return std::task::Poll::Ready(Ok(read));
+        }
+    })
+    .await
+}
+
+/// Marker trait for formats whose encoding is always valid UTF-8 text, such as
+/// [`Json`](crate::format::Json). Text formats allow the data to be encoded to a `String`, see
+/// [`Database::to_string`](crate::Database::to_string).
+pub trait TextFormat<T>: Format<T> {}
+
+/// The error variants [`Format::from_buf_read`](crate::format::Format::from_buf_read) can
+/// return.
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError<E> {
+    /// The bytes failed to be read.
+    #[error("failed to read")]
+    Io(#[source] std::io::Error),
+    /// The bytes failed to be decoded by the format.
+    #[error(transparent)]
+    Format(E),
 }
 
+pub use self::custom::Custom;
+
+pub use self::tagged::{FormatId, Tagged, TaggedError};
+
+pub use self::base64::{Base64, Base64Error};
+
+pub use self::checked::{Checked, CheckedError};
+
+pub use self::fallback::{ReadFallback, ReadFallbackError};
+
+#[cfg(any(feature = "json-format", feature = "bincode-format"))]
+pub use self::dynamic::{by_extension, DynFormat, DynFormatError, FormatKind};
+
 #[cfg(feature = "json-format")]
-pub use self::json::Json;
+pub use self::json::{Json, JsonRuntime, JsonSkipNulls};
+
+#[cfg(feature = "json-format")]
+pub use self::ndjson::Ndjson;
 
 #[cfg(feature = "bincode-format")]
 pub use self::bincode::Bincode;
 
 
+mod custom {
+    use super::Format;
+
+    type ToBytes<T, E> = Box<dyn Fn(&T) -> Result<Vec<u8>, E> + Send + Sync>;
+    type FromBytes<T, E> = Box<dyn Fn(Vec<u8>) -> Result<T, E> + Send + Sync>;
+
+    /// A [`Format`](crate::format::Format) delegating to user-supplied functions, for one-off
+    /// formats that do not warrant implementing the trait.
+    ///
+    /// As the format carries the functions, the database must be constructed with an instance
+    /// of it, for example through
+    /// [`Database::from_parts_with_format`](crate::Database::from_parts_with_format).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Custom, backend::Memory};
+    ///
+    /// let shouting_json = Custom::new(
+    ///     |value: &Vec<String>| serde_json::to_vec(value).map(|bytes| bytes.to_ascii_uppercase()),
+    ///     |bytes: Vec<u8>| serde_json::from_slice(&bytes.to_ascii_lowercase()),
+    /// );
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db = Database::from_parts_with_format(
+    ///         vec!["cat".to_owned(), "yak".to_owned()],
+    ///         Memory::default(),
+    ///         shouting_json,
+    ///     );
+    ///     db.save().await.unwrap();
+    ///     db.reload().await.unwrap();
+    ///     db.read(|animals| assert_eq!(animals, &["cat", "yak"])).await;
+    ///
+    ///     let (_, mut backend) = db.into_parts();
+    ///     assert_eq!(backend.take(), br#"["CAT","YAK"]"#);
+    /// });
+    /// ```
+    pub struct Custom<T, E> {
+        to_bytes: ToBytes<T, E>,
+        from_bytes: FromBytes<T, E>,
+    }
+
+    impl<T, E> Custom<T, E> {
+        /// Create the format from a function converting data to bytes, and a function
+        /// converting bytes back to data.
+        pub fn new<S, D>(to_bytes: S, from_bytes: D) -> Self
+        where
+            S: Fn(&T) -> Result<Vec<u8>, E> + Send + Sync + 'static,
+            D: Fn(Vec<u8>) -> Result<T, E> + Send + Sync + 'static,
+        {
+            Self {
+                to_bytes: Box::new(to_bytes),
+                from_bytes: Box::new(from_bytes),
+            }
+        }
+    }
+
+    impl<T, E> std::fmt::Debug for Custom<T, E> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Custom").finish_non_exhaustive()
+        }
+    }
+
+    impl<T, E> Format<T> for Custom<T, E>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        type Error = E;
+
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            (self.to_bytes)(value)
+        }
+        fn from_bytes(&self, data: Vec<u8>) -> Result<T, Self::Error> {
+            (self.from_bytes)(data)
+        }
+    }
+}
+
 #[cfg(feature = "json-format")]
 mod json {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
     use serde::{de::DeserializeOwned, Serialize};
+    use serde_json::Value;
 
-    use super::Format;
+    use super::{Format, FormatId, TextFormat};
 
     #[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
     /// A pretty-printed JSON [`Format`](crate::format::Format).
-    #[derive(Debug, std::default::Default)]
+    ///
+    /// The [canonical encoding](crate::format::Format::to_bytes_canonical) is compact and has its
+    /// object keys sorted. Unit and other zero-sized data are encoded as `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<(), _, Json> = Database::from_parts((), Memory::default());
+    ///     db.save().await.unwrap();
+    ///     db.reload().await.unwrap();
+    ///
+    ///     let (_, mut backend) = db.into_parts();
+    ///     assert_eq!(backend.take(), b"null");
+    /// });
+    /// ```
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use koit::format::{Format, Json};
+    ///
+    /// let mut a = HashMap::new();
+    /// let mut b = HashMap::new();
+    /// for n in 0..10 {
+    ///     a.insert(n.to_string(), n);
+    ///     b.insert((9 - n).to_string(), 9 - n);
+    /// }
+    ///
+    /// let canonical = Json.to_bytes_canonical(&a).unwrap();
+    /// assert_eq!(canonical, Json.to_bytes_canonical(&b).unwrap());
+    /// assert!(canonical.starts_with(br#"{"0":0,"1":1,"2":2,"#));
+    /// ```
+    #[derive(Debug, Clone, Copy, std::default::Default)]
     pub struct Json;
 
+    impl FormatId for Json {
+        const ID: u8 = 1;
+        const NAME: &'static str = "Json";
+    }
+
+    impl<T: DeserializeOwned + Serialize> TextFormat<T> for Json {}
+
     impl<T: DeserializeOwned + Serialize> Format<T> for Json {
         type Error = serde_json::Error;
 
-        fn to_bytes(value: &T) -> Result<Vec<u8>, Self::Error> {
-            Ok(serde_json::to_vec_pretty(value)?)
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            serde_json::to_vec_pretty(value)
+        }
+        fn from_bytes(&self, data: Vec<u8>) -> Result<T, serde_json::Error> {
+            serde_json::from_slice(&data)
+        }
+        fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            serde_json::to_vec(&sort_keys(serde_json::to_value(value)?))
+        }
+    }
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+    /// A pretty-printed JSON [`Format`](crate::format::Format) omitting object fields that are
+    /// `null`, such as `None` options. Decoding is the same as [`Json`](crate::format::Json), so
+    /// the data must accept missing fields where it encodes `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::format::{Format, JsonSkipNulls};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    /// struct Config {
+    ///     name: String,
+    ///     nickname: Option<String>,
+    ///     nested: Option<Box<Config>>,
+    /// }
+    ///
+    /// let config = Config {
+    ///     name: "koit".to_owned(),
+    ///     nickname: None,
+    ///     nested: Some(Box::new(Config { name: "inner".to_owned(), nickname: None, nested: None })),
+    /// };
+    /// let bytes = JsonSkipNulls.to_bytes(&config).unwrap();
+    /// let text = String::from_utf8(bytes.clone()).unwrap();
+    /// assert!(!text.contains("null"));
+    /// assert!(!text.contains("nickname"));
+    /// assert!(text.contains("inner"));
+    ///
+    /// let decoded: Config = JsonSkipNulls.from_bytes(bytes).unwrap();
+    /// assert_eq!(decoded, config);
+    /// ```
+    #[derive(Debug, Clone, Copy, std::default::Default)]
+    pub struct JsonSkipNulls;
+
+    impl<T: DeserializeOwned + Serialize> TextFormat<T> for JsonSkipNulls {}
+
+    impl<T: DeserializeOwned + Serialize> Format<T> for JsonSkipNulls {
+        type Error = serde_json::Error;
+
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            serde_json::to_vec_pretty(&skip_nulls(serde_json::to_value(value)?))
+        }
+        fn from_bytes(&self, data: Vec<u8>) -> Result<T, serde_json::Error> {
+            Json.from_bytes(data)
+        }
+        fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            serde_json::to_vec(&sort_keys(skip_nulls(serde_json::to_value(value)?)))
+        }
+    }
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+    /// A JSON [`Format`](crate::format::Format) that is pretty-printed or compact depending on a
+    /// flag that can be changed at runtime, for example to pretty-print while debugging only.
+    ///
+    /// Clones share the flag, so keep a clone of the format given to the database to change it
+    /// later. By default, output is pretty-printed, like [`Json`](crate::format::Json). Decoding
+    /// is the same either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::JsonRuntime, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let format = JsonRuntime::new(false);
+    ///     let db = Database::from_parts_with_format(vec![1, 2], Memory::default(), format.clone());
+    ///
+    ///     db.save().await.unwrap();
+    ///     assert_eq!(db.backend_bytes().await.unwrap(), b"[1,2]");
+    ///
+    ///     format.set_pretty(true);
+    ///     db.save().await.unwrap();
+    ///     assert_eq!(db.backend_bytes().await.unwrap(), b"[\n  1,\n  2\n]");
+    /// });
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct JsonRuntime {
+        pretty: Arc<AtomicBool>,
+    }
+
+    impl JsonRuntime {
+        /// Creates the format, pretty-printing if `pretty` is set.
+        pub fn new(pretty: bool) -> Self {
+            Self {
+                pretty: Arc::new(AtomicBool::new(pretty)),
+            }
+        }
+
+        /// Set whether output is pretty-printed, for this format and all its clones.
+        pub fn set_pretty(&self, pretty: bool) {
+            self.pretty.store(pretty, Ordering::SeqCst);
+        }
+
+        /// Returns whether output is pretty-printed.
+        pub fn is_pretty(&self) -> bool {
+            self.pretty.load(Ordering::SeqCst)
+        }
+    }
+
+    impl std::default::Default for JsonRuntime {
+        fn default() -> Self {
+            Self::new(true)
+        }
+    }
+
+    impl<T: DeserializeOwned + Serialize> TextFormat<T> for JsonRuntime {}
+
+    impl<T: DeserializeOwned + Serialize> Format<T> for JsonRuntime {
+        type Error = serde_json::Error;
+
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            if self.is_pretty() {
+                serde_json::to_vec_pretty(value)
+            } else {
+                serde_json::to_vec(value)
+            }
+        }
+        fn from_bytes(&self, data: Vec<u8>) -> Result<T, serde_json::Error> {
+            Json.from_bytes(data)
+        }
+        fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            Json.to_bytes_canonical(value)
+        }
+    }
+
+    /// Recursively remove the fields that are `null` from all objects in the value. Array
+    /// elements are kept, as removing them would shift the others.
+    fn skip_nulls(value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(key, value)| (key, skip_nulls(value)))
+                    .collect(),
+            ),
+            Value::Array(values) => Value::Array(values.into_iter().map(skip_nulls).collect()),
+            value => value,
+        }
+    }
+
+    /// Recursively sort the keys of all objects in the value.
+    fn sort_keys(value: Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.into_iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, sort_keys(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+            value => value,
+        }
+    }
+}
+
+#[cfg(feature = "json-format")]
+mod ndjson {
+    use async_trait::async_trait;
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::{read_until, Format, FormatId, ReadError, TextFormat};
+
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+    /// A newline-delimited JSON [`Format`](crate::format::Format) for sequences.
+    ///
+    /// Each element is encoded as compact JSON on its own line, followed by a newline. When
+    /// decoding, blank lines are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::format::{Format, Ndjson};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    /// struct Event {
+    ///     id: u32,
+    /// }
+    ///
+    /// let events = vec![Event { id: 1 }, Event { id: 2 }];
+    /// let bytes = Ndjson.to_bytes(&events).unwrap();
+    /// assert_eq!(bytes, b"{\"id\":1}\n{\"id\":2}\n");
+    ///
+    /// let decoded: Vec<Event> = Ndjson.from_bytes(bytes).unwrap();
+    /// assert_eq!(decoded, events);
+    /// ```
+    #[derive(Debug, Clone, Copy, std::default::Default)]
+    pub struct Ndjson;
+
+    impl FormatId for Ndjson {
+        const ID: u8 = 2;
+        const NAME: &'static str = "Ndjson";
+    }
+
+    impl<T: DeserializeOwned + Serialize + Send> TextFormat<Vec<T>> for Ndjson {}
+
+    #[async_trait]
+    impl<T: DeserializeOwned + Serialize + Send> Format<Vec<T>> for Ndjson {
+        type Error = serde_json::Error;
+
+        fn to_bytes(&self, value: &Vec<T>) -> Result<Vec<u8>, Self::Error> {
+            let mut bytes = Vec::new();
+            for element in value {
+                serde_json::to_writer(&mut bytes, element)?;
+                bytes.push(b'\n');
+            }
+            Ok(bytes)
         }
-        fn from_bytes(data: Vec<u8>) -> Result<T, serde_json::Error> {
-            Ok(serde_json::from_slice(&data)?)
+        fn from_bytes(&self, data: Vec<u8>) -> Result<Vec<T>, Self::Error> {
+            data.split(|&byte| byte == b'\n')
+                .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+                .map(serde_json::from_slice)
+                .collect()
+        }
+        /// Decodes one line at a time, so only the current line is held in memory besides the
+        /// decoded elements.
+        async fn from_buf_read<R>(&self, mut reader: R) -> Result<Vec<T>, ReadError<Self::Error>>
+        where
+            R: tokio::io::AsyncBufRead + Send + Unpin,
+        {
+            let mut elements = Vec::new();
+            let mut line = Vec::new();
+            while read_until(&mut reader, Some(b'\n'), &mut line)
+                .await
+                .map_err(ReadError::Io)?
+                > 0
+            {
+                if !line.iter().all(u8::is_ascii_whitespace) {
+                    elements.push(serde_json::from_slice(&line).map_err(ReadError::Format)?);
+                }
+                line.clear();
+            }
+            Ok(elements)
         }
     }
 }
@@ -56,21 +542,657 @@ mod json {
 mod bincode {
     use serde::{de::DeserializeOwned, Serialize};
 
-    use super::Format;
+    use super::{Format, FormatId};
 
     #[cfg_attr(docsrs, doc(cfg(feature = "bincode-format")))]
     /// A Bincode [`Format`](crate::format::Format).
-    #[derive(Debug, std::default::Default)]
+    ///
+    /// Unit and other zero-sized data are encoded as zero bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Bincode, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<(), _, Bincode> = Database::from_parts((), Memory::default());
+    ///     db.save().await.unwrap();
+    ///     db.reload().await.unwrap();
+    ///
+    ///     let (_, mut backend) = db.into_parts();
+    ///     assert!(backend.take().is_empty());
+    /// });
+    /// ```
+    #[derive(Debug, Clone, Copy, std::default::Default)]
     pub struct Bincode;
 
+    impl FormatId for Bincode {
+        const ID: u8 = 3;
+        const NAME: &'static str = "Bincode";
+    }
+
     impl<T: Serialize + DeserializeOwned> Format<T> for Bincode {
         type Error = bincode::Error;
 
-        fn to_bytes(value: &T) -> Result<Vec<u8>, Self::Error> {
-            Ok(bincode::serialize(value)?)
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            bincode::serialize(value)
+        }
+        fn from_bytes(&self, data: Vec<u8>) -> Result<T, Self::Error> {
+            bincode::deserialize(&data)
+        }
+    }
+}
+
+mod tagged {
+    use thiserror::Error;
+
+    use super::Format;
+
+    /// The magic bytes starting a [tagged](crate::format::Tagged) encoding.
+    const MAGIC: &[u8; 4] = b"KOIT";
+
+    /// The version of the tagged header.
+    const VERSION: u8 = 1;
+
+    /// Formats identified by a unique byte, for use with [`Tagged`](crate::format::Tagged).
+    ///
+    /// The formats provided by Koit use identifiers below 128. Identifiers from 128 upwards are
+    /// free for user-defined formats.
+    pub trait FormatId {
+        /// The byte identifying the format.
+        const ID: u8;
+        /// The human-readable name of the format.
+        const NAME: &'static str;
+    }
+
+    /// Returns the name of the Koit-provided format with the given identifier, if any.
+    fn known_name(id: u8) -> Option<&'static str> {
+        match id {
+            1 => Some("Json"),
+            2 => Some("Ndjson"),
+            3 => Some("Bincode"),
+            _ => None,
+        }
+    }
+
+    /// The error variants the [`Tagged`](crate::format::Tagged) format can return.
+    #[derive(Debug, Error)]
+    pub enum TaggedError<E> {
+        /// The bytes do not start with a Koit header.
+        #[error("missing Koit header")]
+        MissingHeader,
+        /// The header has a version this version of Koit does not understand.
+        #[error("unsupported header version {0}")]
+        UnsupportedVersion(u8),
+        /// The bytes were encoded by a different format.
+        #[error("format mismatch: expected {expected} found {found}")]
+        Mismatch {
+            expected: &'static str,
+            found: String,
+        },
+        /// The inner format failed.
+        #[error(transparent)]
+        Format(E),
+    }
+
+    /// A [`Format`](crate::format::Format) framing another format with a header identifying it.
+    ///
+    /// The header consists of the magic bytes `KOIT`, the [identifier](crate::format::FormatId)
+    /// of the inner format, and the version of the header, for a total of six bytes. When
+    /// decoding, the header is checked before the inner format is invoked, so data written in
+    /// another format is reported as such rather than as a decoding failure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::format::{Bincode, Format, Json, Tagged, TaggedError};
+    ///
+    /// let bytes = Tagged(Bincode).to_bytes(&vec![1u32, 2]).unwrap();
+    /// assert!(bytes.starts_with(b"KOIT"));
+    ///
+    /// let err = Format::<Vec<u32>>::from_bytes(&Tagged(Json), bytes).unwrap_err();
+    /// assert!(matches!(err, TaggedError::Mismatch { .. }));
+    /// assert_eq!(err.to_string(), "format mismatch: expected Json found Bincode");
+    /// ```
+    #[derive(Debug, Clone, Copy, std::default::Default)]
+    pub struct Tagged<F>(pub F);
+
+    impl<F: FormatId> Tagged<F> {
+        fn header() -> [u8; 6] {
+            [MAGIC[0], MAGIC[1], MAGIC[2], MAGIC[3], F::ID, VERSION]
+        }
+    }
+
+    impl<T, F> Format<T> for Tagged<F>
+    where
+        F: Format<T> + FormatId,
+    {
+        type Error = TaggedError<F::Error>;
+
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            let mut bytes = Self::header().to_vec();
+            bytes.extend(self.0.to_bytes(value).map_err(TaggedError::Format)?);
+            Ok(bytes)
+        }
+        fn from_bytes(&self, mut data: Vec<u8>) -> Result<T, Self::Error> {
+            let header = Self::header();
+            if data.len() < header.len() || !data.starts_with(MAGIC) {
+                return Err(TaggedError::MissingHeader);
+            }
+            let (id, version) = (data[4], data[5]);
+            if id != F::ID {
+                return Err(TaggedError::Mismatch {
+                    expected: F::NAME,
+                    found: known_name(id)
+                        .map(str::to_owned)
+                        .unwrap_or_else(|| format!("format {}", id)),
+                });
+            }
+            if version != VERSION {
+                return Err(TaggedError::UnsupportedVersion(version));
+            }
+            data.drain(..header.len());
+            self.0.from_bytes(data).map_err(TaggedError::Format)
+        }
+        fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            let mut bytes = Self::header().to_vec();
+            bytes.extend(
+                self.0
+                    .to_bytes_canonical(value)
+                    .map_err(TaggedError::Format)?,
+            );
+            Ok(bytes)
+        }
+    }
+}
+
+mod base64 {
+    use thiserror::Error;
+
+    use super::{Format, TextFormat};
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    /// The error variants the [`Base64`](crate::format::Base64) format can return.
+    #[derive(Debug, Error)]
+    pub enum Base64Error<E> {
+        /// The bytes are not valid base64.
+        #[error("invalid base64 at byte {0}")]
+        InvalidBase64(usize),
+        /// The inner format failed.
+        #[error(transparent)]
+        Format(E),
+    }
+
+    /// A [`Format`](crate::format::Format) encoding the bytes of another format as base64, using
+    /// the standard alphabet with padding. This allows storing binary formats in text-oriented
+    /// backends.
+    ///
+    /// When decoding, ASCII whitespace is ignored, so the encoding may be wrapped over lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::format::{Base64, Bincode, Format};
+    ///
+    /// let numbers = vec![1u32, 2, 3];
+    /// let bytes = Base64(Bincode).to_bytes(&numbers).unwrap();
+    /// assert!(bytes.is_ascii());
+    ///
+    /// let decoded: Vec<u32> = Base64(Bincode).from_bytes(bytes).unwrap();
+    /// assert_eq!(decoded, numbers);
+    /// ```
+    ///
+    /// ```
+    /// use koit::format::{Base64, Base64Error, Format, Json};
+    ///
+    /// assert_eq!(Base64(Json).to_bytes(&"hi".to_owned()).unwrap(), b"ImhpIg==");
+    ///
+    /// let result: Result<String, _> = Base64(Json).from_bytes(b"Imhp!g==".to_vec());
+    /// assert!(matches!(result, Err(Base64Error::InvalidBase64(4))));
+    /// ```
+    #[derive(Debug, Clone, Copy, std::default::Default)]
+    pub struct Base64<F>(pub F);
+
+    impl<T, F> TextFormat<T> for Base64<F> where F: Format<T> {}
+
+    impl<T, F> Format<T> for Base64<F>
+    where
+        F: Format<T>,
+    {
+        type Error = Base64Error<F::Error>;
+
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            let bytes = self.0.to_bytes(value).map_err(Base64Error::Format)?;
+            Ok(encode(&bytes))
+        }
+        fn from_bytes(&self, data: Vec<u8>) -> Result<T, Self::Error> {
+            let bytes = decode(&data).map_err(Base64Error::InvalidBase64)?;
+            self.0.from_bytes(bytes).map_err(Base64Error::Format)
+        }
+        fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            let bytes = self
+                .0
+                .to_bytes_canonical(value)
+                .map_err(Base64Error::Format)?;
+            Ok(encode(&bytes))
+        }
+    }
+
+    fn encode(bytes: &[u8]) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let group = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |group, (index, &byte)| {
+                    group | u32::from(byte) << (16 - 8 * index)
+                });
+            for index in 0..4 {
+                if index <= chunk.len() {
+                    encoded.push(ALPHABET[(group >> (18 - 6 * index)) as usize & 0x3f]);
+                } else {
+                    encoded.push(b'=');
+                }
+            }
+        }
+        encoded
+    }
+
+    /// Decode base64, returning the offset of the first invalid byte on failure.
+    fn decode(encoded: &[u8]) -> Result<Vec<u8>, usize> {
+        let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3);
+        let mut group = 0u32;
+        let mut digits = 0;
+        let mut padding = 0;
+        for (offset, &byte) in encoded.iter().enumerate() {
+            if byte.is_ascii_whitespace() {
+                continue;
+            }
+            if byte == b'=' {
+                // Padding may only complete a group of at least two digits.
+                if digits + padding < 2 {
+                    return Err(offset);
+                }
+                padding += 1;
+            } else {
+                let value = match ALPHABET.iter().position(|&digit| digit == byte) {
+                    Some(value) if padding == 0 => value as u32,
+                    _ => return Err(offset),
+                };
+                group = group << 6 | value;
+                digits += 1;
+            }
+
+            if digits + padding == 4 {
+                group <<= 6 * padding;
+                decoded.extend_from_slice(&group.to_be_bytes()[1..4 - padding]);
+                group = 0;
+                digits = 0;
+                padding = 0;
+            }
+        }
+        if digits + padding != 0 {
+            return Err(encoded.len());
+        }
+        Ok(decoded)
+    }
+}
+
+mod checked {
+    use std::convert::TryInto;
+
+    use thiserror::Error;
+
+    use super::Format;
+    use crate::crc32;
+
+    /// The error variants the [`Checked`](crate::format::Checked) format can return.
+    #[derive(Debug, Error)]
+    pub enum CheckedError<E> {
+        /// The bytes are too short to contain a checksum.
+        #[error("the data is too short to contain a checksum")]
+        Truncated,
+        /// The checksum does not match the bytes.
+        #[error("checksum mismatch: expected {expected:#010x}, found {found:#010x}")]
+        Mismatch { expected: u32, found: u32 },
+        /// The inner format failed.
+        #[error(transparent)]
+        Format(E),
+    }
+
+    /// A [`Format`](crate::format::Format) appending a CRC-32 checksum to the bytes of another
+    /// format, detecting corruption when decoding.
+    ///
+    /// The checksum is stored as four little-endian bytes, in the same layout as the
+    /// [`Checksummed`](crate::backend::Checksummed) backend. Being part of the format, it
+    /// protects the data on any backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::format::{Checked, CheckedError, Format, Json};
+    ///
+    /// let numbers = vec![1u32, 2, 3];
+    /// let mut bytes = Checked(Json).to_bytes(&numbers).unwrap();
+    /// let decoded: Vec<u32> = Checked(Json).from_bytes(bytes.clone()).unwrap();
+    /// assert_eq!(decoded, numbers);
+    ///
+    /// let last = bytes.len() - 1;
+    /// bytes[last] ^= 0xff;
+    /// let result: Result<Vec<u32>, _> = Checked(Json).from_bytes(bytes);
+    /// assert!(matches!(result, Err(CheckedError::Mismatch { .. })));
+    /// ```
+    #[derive(Debug, Clone, Copy, std::default::Default)]
+    pub struct Checked<F>(pub F);
+
+    fn append_checksum(mut bytes: Vec<u8>) -> Vec<u8> {
+        let checksum = crc32::checksum(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    impl<T, F> Format<T> for Checked<F>
+    where
+        F: Format<T>,
+    {
+        type Error = CheckedError<F::Error>;
+
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            let bytes = self.0.to_bytes(value).map_err(CheckedError::Format)?;
+            Ok(append_checksum(bytes))
+        }
+        fn from_bytes(&self, mut data: Vec<u8>) -> Result<T, Self::Error> {
+            if data.len() < 4 {
+                return Err(CheckedError::Truncated);
+            }
+            let stored = data.split_off(data.len() - 4);
+            let expected = u32::from_le_bytes(stored[..].try_into().unwrap());
+            let found = crc32::checksum(&data);
+            if expected != found {
+                return Err(CheckedError::Mismatch { expected, found });
+            }
+            self.0.from_bytes(data).map_err(CheckedError::Format)
+        }
+        fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            let bytes = self
+                .0
+                .to_bytes_canonical(value)
+                .map_err(CheckedError::Format)?;
+            Ok(append_checksum(bytes))
+        }
+    }
+}
+
+mod fallback {
+    use thiserror::Error;
+
+    use super::{Format, TextFormat};
+
+    /// The error variants the [`ReadFallback`](crate::format::ReadFallback) format can return.
+    #[derive(Debug, Error)]
+    pub enum ReadFallbackError<P, S> {
+        /// The primary format failed to encode the data.
+        #[error(transparent)]
+        Primary(P),
+        /// Neither format could decode the bytes.
+        #[error("neither format could decode the data: {primary}; {secondary}")]
+        Both { primary: P, secondary: S },
+    }
+
+    /// A [`Format`](crate::format::Format) decoding with a primary format, falling back to a
+    /// secondary format if that fails, and always encoding with the primary format. This eases
+    /// migrating data from one format to another in place: data in the old, secondary format is
+    /// still read, and rewritten in the new, primary format on the next save.
+    ///
+    /// Bytes valid in the primary format are never decoded by the secondary format, so the
+    /// secondary format's data should not happen to be valid in the primary format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, backend::Memory, format::{Bincode, Format, Json, ReadFallback}};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let old = Bincode.to_bytes(&vec![1u32, 2]).unwrap();
+    ///     let db: Database<Vec<u32>, _, ReadFallback<Json, Bincode>> =
+    ///         Database::from_parts(Vec::new(), Memory::from(old));
+    ///
+    ///     db.reload().await.unwrap();
+    ///     assert_eq!(db.read(Vec::clone).await, vec![1, 2]);
+    ///
+    ///     db.save().await.unwrap();
+    ///     assert_eq!(db.backend_bytes().await.unwrap(), Json.to_bytes(&vec![1u32, 2]).unwrap());
+    /// });
+    /// ```
+    #[derive(Debug, Clone, Copy, std::default::Default)]
+    pub struct ReadFallback<P, S>(pub P, pub S);
+
+    impl<T, P, S> TextFormat<T> for ReadFallback<P, S>
+    where
+        P: TextFormat<T>,
+        S: Format<T>,
+    {
+    }
+
+    impl<T, P, S> Format<T> for ReadFallback<P, S>
+    where
+        P: Format<T>,
+        S: Format<T>,
+    {
+        type Error = ReadFallbackError<P::Error, S::Error>;
+
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            self.0.to_bytes(value).map_err(ReadFallbackError::Primary)
+        }
+        fn from_bytes(&self, data: Vec<u8>) -> Result<T, Self::Error> {
+            let primary = match self.0.from_bytes(data.clone()) {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            self.1
+                .from_bytes(data)
+                .map_err(|secondary| ReadFallbackError::Both { primary, secondary })
+        }
+        fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            self.0
+                .to_bytes_canonical(value)
+                .map_err(ReadFallbackError::Primary)
+        }
+    }
+}
+
+#[cfg(any(feature = "json-format", feature = "bincode-format"))]
+mod dynamic {
+    use std::path::Path;
+
+    use serde::{de::DeserializeOwned, Serialize};
+    use thiserror::Error;
+
+    use super::Format;
+
+    /// The formats that can be chosen at runtime, for use with
+    /// [`DynFormat`](crate::format::DynFormat).
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "json-format", feature = "bincode-format")))
+    )]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum FormatKind {
+        /// The [`Json`](crate::format::Json) format, for the `json` extension.
+        #[cfg(feature = "json-format")]
+        Json,
+        /// The [`Bincode`](crate::format::Bincode) format, for the `bin` and `bincode`
+        /// extensions.
+        #[cfg(feature = "bincode-format")]
+        Bincode,
+    }
+
+    impl FormatKind {
+        /// Returns the format for the given file extension, ignoring case, if any.
+        pub fn from_extension(extension: &str) -> Option<Self> {
+            match extension.to_ascii_lowercase().as_str() {
+                #[cfg(feature = "json-format")]
+                "json" => Some(FormatKind::Json),
+                #[cfg(feature = "bincode-format")]
+                "bin" | "bincode" => Some(FormatKind::Bincode),
+                _ => None,
+            }
+        }
+
+        /// Returns the lowercase name of the format, as accepted by
+        /// [`from_str`](std::str::FromStr::from_str).
+        pub fn name(&self) -> &'static str {
+            match self {
+                #[cfg(feature = "json-format")]
+                FormatKind::Json => "json",
+                #[cfg(feature = "bincode-format")]
+                FormatKind::Bincode => "bincode",
+            }
+        }
+    }
+
+    /// Parses the name of a format, ignoring case, such as from a command-line flag.
+    impl std::str::FromStr for FormatKind {
+        type Err = DynFormatError;
+
+        fn from_str(name: &str) -> Result<Self, Self::Err> {
+            match name.to_ascii_lowercase().as_str() {
+                #[cfg(feature = "json-format")]
+                "json" => Ok(FormatKind::Json),
+                #[cfg(feature = "bincode-format")]
+                "bincode" => Ok(FormatKind::Bincode),
+                _ => Err(DynFormatError::UnknownName(name.to_owned())),
+            }
+        }
+    }
+
+    /// Returns the format for the extension of the path, if any. See
+    /// [`FormatKind::from_extension`](crate::format::FormatKind::from_extension).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::format::{by_extension, FormatKind};
+    ///
+    /// assert_eq!(by_extension("config.json"), Some(FormatKind::Json));
+    /// assert_eq!(by_extension("cache.BIN"), Some(FormatKind::Bincode));
+    /// assert_eq!(by_extension("notes.txt"), None);
+    /// assert_eq!(by_extension("json"), None);
+    /// ```
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "json-format", feature = "bincode-format")))
+    )]
+    pub fn by_extension<P>(path: P) -> Option<FormatKind>
+    where
+        P: AsRef<Path>,
+    {
+        path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(FormatKind::from_extension)
+    }
+
+    /// The error variants the [`DynFormat`](crate::format::DynFormat) format can return.
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "json-format", feature = "bincode-format")))
+    )]
+    #[derive(Debug, Error)]
+    pub enum DynFormatError {
+        /// The [`Json`](crate::format::Json) format failed.
+        #[cfg(feature = "json-format")]
+        #[error(transparent)]
+        Json(serde_json::Error),
+        /// The [`Bincode`](crate::format::Bincode) format failed.
+        #[cfg(feature = "bincode-format")]
+        #[error(transparent)]
+        Bincode(bincode::Error),
+        /// No format is known for the file extension.
+        #[error("no format is known for the extension of `{0}`")]
+        UnknownExtension(std::path::PathBuf),
+        /// No format is known by the name.
+        #[error("unknown format `{0}`")]
+        UnknownName(String),
+    }
+
+    /// A [`Format`](crate::format::Format) chosen at runtime, such as by a command-line flag or
+    /// the extension of a file. It dispatches to the [enabled format](crate::format::FormatKind)
+    /// it holds, and reports the errors of all formats as a
+    /// [`DynFormatError`](crate::format::DynFormatError).
+    ///
+    /// See [`FileDatabase::load_from_path_by_extension`](crate::Database::load_from_path_by_extension).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, backend::Memory, format::{DynFormat, Format, FormatKind}};
+    ///
+    /// futures::executor::block_on(async {
+    ///     for flag in &["json", "bincode"] {
+    ///         let format = DynFormat::from(flag.parse::<FormatKind>().unwrap());
+    ///         let db: Database<Vec<u32>, _, _> =
+    ///             Database::from_parts_with_format(vec![1, 2], Memory::default(), format);
+    ///         db.save().await.unwrap();
+    ///
+    ///         db.write(Vec::clear).await;
+    ///         db.reload().await.unwrap();
+    ///         assert_eq!(db.read(Vec::clone).await, vec![1, 2]);
+    ///     }
+    ///
+    ///     let format = DynFormat(FormatKind::Json);
+    ///     assert_eq!(format.to_bytes(&vec![1u32]).unwrap(), b"[\n  1\n]");
+    ///     assert!("toml".parse::<FormatKind>().is_err());
+    /// });
+    /// ```
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "json-format", feature = "bincode-format")))
+    )]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct DynFormat(pub FormatKind);
+
+    impl From<FormatKind> for DynFormat {
+        fn from(kind: FormatKind) -> Self {
+            Self(kind)
+        }
+    }
+
+    impl<T: Serialize + DeserializeOwned> Format<T> for DynFormat {
+        type Error = DynFormatError;
+
+        fn to_bytes(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            match self.0 {
+                #[cfg(feature = "json-format")]
+                FormatKind::Json => super::Json.to_bytes(value).map_err(DynFormatError::Json),
+                #[cfg(feature = "bincode-format")]
+                FormatKind::Bincode => super::Bincode
+                    .to_bytes(value)
+                    .map_err(DynFormatError::Bincode),
+            }
+        }
+        fn from_bytes(&self, data: Vec<u8>) -> Result<T, Self::Error> {
+            match self.0 {
+                #[cfg(feature = "json-format")]
+                FormatKind::Json => super::Json.from_bytes(data).map_err(DynFormatError::Json),
+                #[cfg(feature = "bincode-format")]
+                FormatKind::Bincode => super::Bincode
+                    .from_bytes(data)
+                    .map_err(DynFormatError::Bincode),
+            }
         }
-        fn from_bytes(data: Vec<u8>) -> Result<T, Self::Error> {
-            Ok(bincode::deserialize(&data)?)
+        fn to_bytes_canonical(&self, value: &T) -> Result<Vec<u8>, Self::Error> {
+            match self.0 {
+                #[cfg(feature = "json-format")]
+                FormatKind::Json => super::Json
+                    .to_bytes_canonical(value)
+                    .map_err(DynFormatError::Json),
+                #[cfg(feature = "bincode-format")]
+                FormatKind::Bincode => super::Bincode
+                    .to_bytes_canonical(value)
+                    .map_err(DynFormatError::Bincode),
+            }
         }
     }
 }
@@ -0,0 +1,396 @@
+//! Decoding JSON values while recording the first field the data ignored, for
+//! [`load_from_path_strict`](crate::Database::load_from_path_strict).
+//!
+//! Fields are ignored when the data type does not consume them, which serde signals by
+//! deserializing them as [`IgnoredAny`](serde::de::IgnoredAny). Fields the data does consume,
+//! such as aliased fields or fields skipped when encoding, are not reported.
+
+use std::cell::RefCell;
+use std::fmt;
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Error, Value};
+
+/// Decode the value, returning the data and the dotted path of the first field that the data
+/// ignored, if any.
+pub(crate) fn from_value<D>(value: &Value) -> Result<(D, Option<String>), Error>
+where
+    D: DeserializeOwned,
+{
+    let ignored = RefCell::new(None);
+    let data = D::deserialize(Tracked {
+        value,
+        path: Path::Root,
+        ignored: &ignored,
+    })?;
+    Ok((data, ignored.into_inner()))
+}
+
+/// The location of a value within the decoded value.
+#[derive(Clone, Copy)]
+enum Path<'a> {
+    Root,
+    Field(&'a Path<'a>, &'a str),
+    Index(&'a Path<'a>, usize),
+}
+
+impl fmt::Display for Path<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (parent, segment): (_, &dyn fmt::Display) = match self {
+            Path::Root => return Ok(()),
+            Path::Field(parent, name) => (parent, name),
+            Path::Index(parent, index) => (parent, index),
+        };
+        match parent {
+            Path::Root => write!(f, "{}", segment),
+            parent => write!(f, "{}.{}", parent, segment),
+        }
+    }
+}
+
+/// A deserializer over a JSON value, recording where the data ignores values.
+struct Tracked<'de, 'a> {
+    value: &'de Value,
+    path: Path<'a>,
+    ignored: &'a RefCell<Option<String>>,
+}
+
+impl Tracked<'_, '_> {
+    fn invalid_type(&self, expected: &dyn de::Expected) -> Error {
+        let unexpected = match self.value {
+            Value::Null => de::Unexpected::Unit,
+            Value::Bool(value) => de::Unexpected::Bool(*value),
+            Value::Number(_) => de::Unexpected::Other("number"),
+            Value::String(value) => de::Unexpected::Str(value),
+            Value::Array(_) => de::Unexpected::Seq,
+            Value::Object(_) => de::Unexpected::Map,
+        };
+        de::Error::invalid_type(unexpected, expected)
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Tracked<'de, 'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(value) => visitor.visit_bool(*value),
+            Value::Number(number) => {
+                if let Some(number) = number.as_u64() {
+                    visitor.visit_u64(number)
+                } else if let Some(number) = number.as_i64() {
+                    visitor.visit_i64(number)
+                } else {
+                    visitor.visit_f64(number.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::String(value) => visitor.visit_borrowed_str(value),
+            Value::Array(values) => {
+                let mut elements = Elements {
+                    values: values.iter().enumerate(),
+                    path: &self.path,
+                    ignored: self.ignored,
+                };
+                let result = visitor.visit_seq(&mut elements)?;
+                match elements.values.len() {
+                    0 => Ok(result),
+                    _ => Err(de::Error::invalid_length(
+                        values.len(),
+                        &"fewer elements in array",
+                    )),
+                }
+            }
+            Value::Object(fields) => {
+                let mut entries = Entries {
+                    fields: fields.iter(),
+                    value: None,
+                    path: &self.path,
+                    ignored: self.ignored,
+                };
+                let result = visitor.visit_map(&mut entries)?;
+                match entries.fields.len() {
+                    0 => Ok(result),
+                    _ => Err(de::Error::invalid_length(
+                        fields.len(),
+                        &"fewer elements in map",
+                    )),
+                }
+            }
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            Value::Object(fields) if fields.len() == 1 => {
+                let (variant, value) = fields.iter().next().unwrap();
+                visitor.visit_enum(Variant {
+                    variant,
+                    value,
+                    path: &self.path,
+                    ignored: self.ignored,
+                })
+            }
+            _ => Err(self.invalid_type(&"a string or a map with a single key")),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut ignored = self.ignored.borrow_mut();
+        if ignored.is_none() {
+            *ignored = Some(self.path.to_string());
+        }
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// The elements of an array.
+struct Elements<'a, I> {
+    values: I,
+    path: &'a Path<'a>,
+    ignored: &'a RefCell<Option<String>>,
+}
+
+impl<'de, 'a, I> de::SeqAccess<'de> for Elements<'a, I>
+where
+    I: ExactSizeIterator<Item = (usize, &'de Value)>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.values.next() {
+            Some((index, value)) => seed
+                .deserialize(Tracked {
+                    value,
+                    path: Path::Index(self.path, index),
+                    ignored: self.ignored,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+/// The entries of an object.
+struct Entries<'de, 'a> {
+    fields: serde_json::map::Iter<'de>,
+    value: Option<(&'de str, &'de Value)>,
+    path: &'a Path<'a>,
+    ignored: &'a RefCell<Option<String>>,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for Entries<'de, 'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.fields.next() {
+            Some((name, value)) => {
+                self.value = Some((name, value));
+                seed.deserialize(Key(name)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (name, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value is missing"))?;
+        seed.deserialize(Tracked {
+            value,
+            path: Path::Field(self.path, name),
+            ignored: self.ignored,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+/// An enum variant written as an object with a single key.
+struct Variant<'de, 'a> {
+    variant: &'de str,
+    value: &'de Value,
+    path: &'a Path<'a>,
+    ignored: &'a RefCell<Option<String>>,
+}
+
+impl<'de: 'a, 'a> de::EnumAccess<'de> for Variant<'de, 'a> {
+    type Error = Error;
+    type Variant = Tracked<'de, 'a>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(Key(self.variant))?;
+        let value = Tracked {
+            value: self.value,
+            path: Path::Field(self.path, self.variant),
+            ignored: self.ignored,
+        };
+        Ok((variant, value))
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for Tracked<'de, 'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+/// A deserializer over an object key. JSON keys are always strings, so keys of other types,
+/// such as the integer keys of a map, are parsed from the string.
+struct Key<'de>(&'de str);
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => visitor.visit_borrowed_str(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Key<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
@@ -0,0 +1,205 @@
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+use crate::{backend, FileDatabase, Format, KoitError};
+
+/// A file-backed database with a write-ahead log.
+///
+/// Every write appends the resulting data, encoded by the format, to a log next to the backing
+/// file, at the backing file's path with `.wal` appended. Saving checkpoints: the backing file is
+/// written and the log emptied. When the database is opened and the log holds entries, the
+/// last entry is newer than the backing file, and its data is adopted. Writes that completed
+/// thus survive a crash, even if the database was not saved.
+///
+/// Each entry is the encoded data prefixed by its length in bytes as a little-endian `u64`. An
+/// entry that was only partially written is discarded when opening.
+///
+/// Note: this requires its futures to be executed on the Tokio runtime.
+///
+/// # Examples
+///
+/// ```
+/// use koit::{JournaledFileDatabase, format::Json};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = std::env::temp_dir().join("koit-doc-journaled.json");
+///     let db = JournaledFileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+///     db.write(|numbers| numbers.push(1)).await?;
+///     db.save().await?;
+///     db.write(|numbers| numbers.push(2)).await?;
+///
+///     // Crash before saving: the backing file is stale, but the log is not.
+///     drop(db);
+///     assert_eq!(std::fs::read_to_string(&path)?, "[\n  1\n]");
+///
+///     let db = JournaledFileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+///     assert_eq!(db.read(Vec::clone).await, vec![1, 2]);
+///
+///     db.save().await?;
+///     assert_eq!(std::fs::metadata(db.wal_path())?.len(), 0);
+///
+///     std::fs::remove_file(&path)?;
+///     std::fs::remove_file(db.wal_path())?;
+///     Ok(())
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+#[derive(Debug)]
+pub struct JournaledFileDatabase<D, F> {
+    db: FileDatabase<D, F>,
+    wal: Mutex<tokio::fs::File>,
+    wal_path: PathBuf,
+}
+
+impl<D, F> JournaledFileDatabase<D, F>
+where
+    F: Format<D>,
+{
+    /// Construct the database from the given path, creating the backing file with default data
+    /// if it does not exist. See
+    /// [`FileDatabase::load_from_path_or_default`](crate::Database::load_from_path_or_default).
+    ///
+    /// If the log holds entries, the data of the last entry is adopted.
+    ///
+    /// # Errors
+    ///
+    /// If the backing file or the log cannot be read, or the format cannot decode the data, an
+    /// error variant is returned.
+    pub async fn load_from_path_or_default<P>(path: P) -> Result<Self, KoitError>
+    where
+        P: AsRef<Path>,
+        D: Default,
+        F: Default,
+    {
+        let db: FileDatabase<D, F> = FileDatabase::load_from_path_or_default(&path).await?;
+        let wal_path = backend::with_suffix(path.as_ref(), ".wal");
+        let mut wal = tokio::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&wal_path)
+            .await
+            .map_err(|err| KoitError::BackendCreation(err.into()))?;
+
+        let log = tokio::fs::read(&wal_path)
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+        let (last, complete) = last_entry(&log);
+        if let Some(entry) = last {
+            let data = db
+                .format
+                .from_bytes(entry.to_vec())
+                .map_err(|err| KoitError::FromFormat(err.into()))?;
+            db.replace(data).await;
+        }
+        // Drop a partially written entry, so new entries are appended after the complete ones.
+        wal.set_len(complete as u64)
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))?;
+        wal.seek(std::io::SeekFrom::End(0))
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))?;
+
+        Ok(Self {
+            db,
+            wal: Mutex::new(wal),
+            wal_path,
+        })
+    }
+
+    /// Returns the path of the write-ahead log.
+    pub fn wal_path(&self) -> &Path {
+        &self.wal_path
+    }
+
+    /// Returns the underlying database. Writes made directly to it are not logged.
+    pub fn database(&self) -> &FileDatabase<D, F> {
+        &self.db
+    }
+
+    /// Read the data contained in the database. See [`Database::read`](crate::Database::read).
+    pub async fn read<T, R>(&self, task: T) -> R
+    where
+        T: FnOnce(&D) -> R,
+    {
+        self.db.read(task).await
+    }
+
+    /// Write to the data contained in the database, and append the resulting data to the log.
+    /// See [`Database::write`](crate::Database::write).
+    ///
+    /// # Errors
+    ///
+    /// - If the underlying database was [finalized](crate::Database::finalize),
+    ///   [`KoitError::Finalized`](crate::KoitError::Finalized) is returned and the task is not run.
+    /// - If the data failed to be encoded by the format, or appended to the log, an error variant
+    ///   is returned. The write remains applied in memory.
+    pub async fn write<T, R>(&self, task: T) -> Result<R, KoitError>
+    where
+        T: FnOnce(&mut D) -> R,
+    {
+        let mut wal = self.wal.lock().await;
+        self.db.ensure_not_finalized()?;
+        let (result, bytes) = self
+            .db
+            .write_then_read(task, |data, result| (result, self.db.format.to_bytes(data)))
+            .await;
+        let bytes = bytes.map_err(|err| KoitError::ToFormat(err.into()))?;
+
+        let mut entry = Vec::with_capacity(8 + bytes.len());
+        entry.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        entry.extend_from_slice(&bytes);
+        wal.write_all(&entry)
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))?;
+        wal.sync_data()
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))?;
+        Ok(result)
+    }
+
+    /// Flush the data contained in the database to the backing file, and empty the log.
+    ///
+    /// # Errors
+    ///
+    /// If the data failed to be saved, or the log failed to be emptied, an error variant is
+    /// returned. The log is only emptied once the data is saved.
+    pub async fn save(&self) -> Result<(), KoitError> {
+        let mut wal = self.wal.lock().await;
+        self.db.save().await?;
+        wal.set_len(0)
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))?;
+        wal.seek(std::io::SeekFrom::Start(0))
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))?;
+        Ok(())
+    }
+
+    /// Consume the journaled database and return the underlying database.
+    pub fn into_inner(self) -> FileDatabase<D, F> {
+        self.db
+    }
+}
+
+/// Returns the last complete entry of the log, if any, and the length of the complete entries.
+fn last_entry(log: &[u8]) -> (Option<&[u8]>, usize) {
+    let mut last = None;
+    let mut offset = 0;
+    while log.len() - offset >= 8 {
+        let mut len_bytes = [0; 8];
+        len_bytes.copy_from_slice(&log[offset..offset + 8]);
+        let len = match usize::try_from(u64::from_le_bytes(len_bytes)) {
+            Ok(len) if len <= log.len() - offset - 8 => len,
+            _ => break,
+        };
+        last = Some(&log[offset + 8..offset + 8 + len]);
+        offset += 8 + len;
+    }
+    (last, offset)
+}
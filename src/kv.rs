@@ -0,0 +1,181 @@
+//! A typed key-value store, for the common case of a database holding a map of settings.
+//!
+//! # Examples
+//!
+//! ```
+//! use koit::{Database, format::Json, backend::Memory, kv::KvStore};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let store: KvStore<_, Json> = KvStore::new(Database::from_parts(
+//!         Default::default(),
+//!         Memory::default(),
+//!     ));
+//!
+//!     store.set("volume", &11u8).await?;
+//!     store.set("name", &"koit").await?;
+//!     store.set("tags", &vec!["fast", "small"]).await?;
+//!
+//!     assert_eq!(store.get::<u8>("volume").await?, Some(11));
+//!     assert_eq!(store.get::<String>("name").await?, Some("koit".to_owned()));
+//!     assert_eq!(store.get::<Vec<String>>("tags").await?.unwrap().len(), 2);
+//!     assert_eq!(store.get::<u8>("missing").await?, None);
+//!
+//!     store.remove("volume").await?;
+//!     assert_eq!(store.get::<u8>("volume").await?, None);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{Backend, Database, Format, KoitError};
+
+/// The data held by a [`KvStore`](crate::kv::KvStore).
+pub type Map = HashMap<String, Value>;
+
+/// A typed key-value store on top of a [`Database`](crate::Database) holding a
+/// [map](crate::kv::Map) of JSON values. Each value is converted independently, so values of
+/// different types can be stored under different keys.
+///
+/// Changes are saved to the backend immediately.
+///
+/// # Examples
+///
+/// See the [kv module documentation](crate::kv).
+#[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+#[derive(Debug)]
+pub struct KvStore<B, F> {
+    db: Database<Map, B, F>,
+}
+
+impl<B, F> KvStore<B, F>
+where
+    B: Backend,
+    F: Format<Map>,
+{
+    /// Create the store on top of the given database.
+    pub fn new(db: Database<Map, B, F>) -> Self {
+        Self { db }
+    }
+
+    /// Get the value stored under the given key, or `None` if there is no such value.
+    ///
+    /// # Errors
+    ///
+    /// If the stored value failed to be converted to `T`, an error variant is returned.
+    pub async fn get<T>(&self, key: &str) -> Result<Option<T>, KoitError>
+    where
+        T: DeserializeOwned,
+    {
+        let value = self.db.read(|map| map.get(key).cloned()).await;
+        value
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|err| KoitError::FromFormat(err.into()))
+    }
+
+    /// Store the value under the given key, replacing any value already stored under it, and
+    /// save the database.
+    ///
+    /// # Errors
+    ///
+    /// - If the value failed to be converted, an error variant is returned. The store is left
+    ///   unchanged.
+    /// - If the database failed to be saved, an error variant is returned. The change is undone,
+    ///   unless the store was written to again in the meantime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use koit::{Database, backend::Backend, format::Json, kv::KvStore};
+    ///
+    /// /// A backend failing every write.
+    /// struct ReadOnly;
+    ///
+    /// #[async_trait]
+    /// impl Backend for ReadOnly {
+    ///     type Error = std::io::Error;
+    ///
+    ///     async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+    ///         Ok(Vec::new())
+    ///     }
+    ///     async fn write(&mut self, _data: Vec<u8>) -> Result<(), Self::Error> {
+    ///         Err(std::io::ErrorKind::PermissionDenied.into())
+    ///     }
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let store: KvStore<_, Json> =
+    ///         KvStore::new(Database::from_parts(Default::default(), ReadOnly));
+    ///
+    ///     assert!(store.set("volume", &11u8).await.is_err());
+    ///     assert_eq!(store.get::<u8>("volume").await.unwrap(), None);
+    ///
+    ///     // Nothing was saved, and the store is back to the data the backend held.
+    ///     assert!(!store.database().unsaved_changes());
+    /// });
+    /// ```
+    pub async fn set<T>(&self, key: impl Into<String>, value: &T) -> Result<(), KoitError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = key.into();
+        let value = serde_json::to_value(value).map_err(|err| KoitError::ToFormat(err.into()))?;
+        let mut backend = self.db.backend.lock().await;
+        let (previous, version) = self
+            .db
+            .try_write_versioned(|map| map.insert(key.clone(), value))
+            .await?;
+        if let Err(err) = self.db.save_to(&mut backend).await {
+            self.db
+                .undo_write(version, |map| {
+                    match previous {
+                        Some(previous) => map.insert(key, previous),
+                        None => map.remove(&key),
+                    };
+                })
+                .await;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Remove the value stored under the given key and save the database, returning whether a
+    /// value was removed.
+    ///
+    /// # Errors
+    ///
+    /// If the database failed to be saved, an error variant is returned. The removed value is
+    /// restored, unless the store was written to again in the meantime.
+    pub async fn remove(&self, key: &str) -> Result<bool, KoitError> {
+        let mut backend = self.db.backend.lock().await;
+        let (removed, version) = self.db.try_write_versioned(|map| map.remove(key)).await?;
+        if let Err(err) = self.db.save_to(&mut backend).await {
+            if let Some(removed) = removed {
+                self.db
+                    .undo_write(version, |map| {
+                        map.insert(key.to_owned(), removed);
+                    })
+                    .await;
+            }
+            return Err(err);
+        }
+        Ok(removed.is_some())
+    }
+
+    /// Returns a reference to the underlying database.
+    pub fn database(&self) -> &Database<Map, B, F> {
+        &self.db
+    }
+
+    /// Consume the store and return the underlying database.
+    pub fn into_database(self) -> Database<Map, B, F> {
+        self.db
+    }
+}
@@ -37,12 +37,34 @@
 //! and [Bincode](crate::format::Bincode) formatters. You can also define your own storage
 //! [format](crate::format) or [backend](crate::backend).
 //!
-//! Note that the file-backed database requires the Tokio 0.3 runtime to function.
+//! Note that the file-backed database requires the Tokio runtime to function.
+//!
+//! # Runtimes
+//!
+//! The [`Database`](crate::Database) itself does not depend on any particular async runtime: its
+//! synchronization primitives work on any executor. Only backends performing I/O, such as the
+//! [file backend](crate::backend::File), may require a specific runtime. For example, a database
+//! with a [memory backend](crate::backend::Memory) can be driven by a simple executor:
+//!
+//! ```
+//! use koit::{Database, format::Json, backend::Memory};
+//!
+//! let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), Memory::default());
+//!
+//! futures::executor::block_on(async {
+//!     db.write(|numbers| numbers.push(42)).await;
+//!     db.save().await.unwrap();
+//!     db.reload().await.unwrap();
+//!
+//!     assert_eq!(db.read(|numbers| numbers.clone()).await, vec![42]);
+//! });
+//! ```
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 use std::future::Future;
-use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::{Mutex, RwLock};
 
 mod error;
@@ -54,18 +76,103 @@ pub use backend::Backend;
 pub mod format;
 pub use format::Format;
 
+#[cfg(feature = "json-format")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+pub mod kv;
+
+#[cfg(any(feature = "file-backend", feature = "testing"))]
+mod clock;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub use clock::{Clock, MockClock, SystemClock};
+
+mod counter;
+pub use counter::CounterDatabase;
+
+mod crc32;
+
+#[cfg(all(feature = "file-backend", feature = "json-format"))]
+mod ignored;
+
+#[cfg(feature = "file-backend")]
+mod journaled;
+#[cfg(feature = "file-backend")]
+pub use journaled::JournaledFileDatabase;
+
+mod notify;
+pub use notify::BatchGuard;
+use notify::Notifier;
+
+#[cfg(feature = "json-format")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+pub mod patch;
+
+mod sha256;
+
+mod snapshot;
+use snapshot::Snapshot;
+
+mod timing;
+#[cfg(feature = "lock-stats")]
+pub use timing::LockWaitStats;
+use timing::Timings;
+pub use timing::{ReloadTiming, SaveTiming};
+
+#[cfg(feature = "file-backend")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+pub mod transaction;
+
+/// Returns the Cargo features Koit was compiled with, such as `"json-format"`, for diagnostics
+/// and bug reports.
+///
+/// # Examples
+///
+/// ```
+/// let features = koit::enabled_features();
+/// assert_eq!(features.contains(&"json-format"), cfg!(feature = "json-format"));
+/// assert!(!features.contains(&"unknown"));
+/// ```
+pub fn enabled_features() -> Vec<&'static str> {
+    let features = [
+        ("file-backend", cfg!(feature = "file-backend")),
+        ("stream-backend", cfg!(feature = "stream-backend")),
+        ("unix-socket-backend", cfg!(feature = "unix-socket-backend")),
+        ("json-format", cfg!(feature = "json-format")),
+        ("bincode-format", cfg!(feature = "bincode-format")),
+        ("testing", cfg!(feature = "testing")),
+        ("lock-stats", cfg!(feature = "lock-stats")),
+    ];
+    features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// The size of the chunks [`Database::save_with_progress`] writes to the backend.
+const SAVE_CHUNK_SIZE: usize = 64 * 1024;
+
 /// The Koit database.
 ///
 /// The database provides reading, writing, saving and reloading functionality.
 /// It uses a reader-writer lock on the internal data structure, allowing
 /// concurrent access by readers, while writers are given exclusive access.
 ///
-/// It requires a [`Format`](crate::format::Format) marker type
+/// It requires a [`Format`](crate::format::Format) to convert the data to and from bytes.
 #[derive(Debug)]
 pub struct Database<D, B, F> {
     data: RwLock<D>,
     backend: Mutex<B>,
-    _format: PhantomData<F>,
+    notifier: Notifier,
+    timings: std::sync::Mutex<Timings>,
+    snapshot: Option<Snapshot<D>>,
+    initialized_from_default: bool,
+    version: AtomicU64,
+    saved_version: AtomicU64,
+    finalized: AtomicBool,
+    /// The hash of the bytes last loaded by `reload_if_changed`, and the version they loaded as.
+    loaded_hash: std::sync::Mutex<Option<([u8; 32], u64)>>,
+    format: F,
 }
 
 impl<D, B, F> Database<D, B, F>
@@ -74,11 +181,127 @@ where
     F: Format<D>,
 {
     /// Create a database from its constituents.
-    pub fn from_parts(data: D, backend: B) -> Self {
+    pub fn from_parts(data: D, backend: B) -> Self
+    where
+        F: Default,
+    {
+        Self::from_parts_with_format(data, backend, F::default())
+    }
+
+    /// Create a database from its constituents, using the given format instance. This is
+    /// required for formats carrying configuration or state.
+    pub fn from_parts_with_format(data: D, backend: B, format: F) -> Self {
         Self {
             data: RwLock::new(data),
             backend: Mutex::new(backend),
-            _format: PhantomData,
+            notifier: Notifier::new(),
+            timings: std::sync::Mutex::new(Timings::default()),
+            snapshot: None,
+            initialized_from_default: false,
+            version: AtomicU64::new(0),
+            saved_version: AtomicU64::new(0),
+            finalized: AtomicBool::new(false),
+            loaded_hash: std::sync::Mutex::new(None),
+            format,
+        }
+    }
+
+    /// Create a database from its constituents, with reads served from a snapshot of the data.
+    ///
+    /// Readers of a snapshotting database never wait on writers: they observe the data as it was
+    /// after the last completed write, even while another write is in progress. Every write
+    /// clones the data to update the snapshot, trading memory and write throughput for read
+    /// availability.
+    ///
+    /// Changes made directly through the [data lock](crate::Database::get_data_lock) or
+    /// [`get_data_mut`](crate::Database::get_data_mut) are not observed by readers until the next
+    /// write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Database<u32, _, Json> = Database::new_snapshotting(1, Memory::default());
+    ///     let (commit, committed) = tokio::sync::oneshot::channel();
+    ///
+    ///     // A slow write, which only completes once `commit` is sent.
+    ///     let write = db.write_and_then(|n| {
+    ///         *n = 2;
+    ///         committed
+    ///     });
+    ///     let read = async {
+    ///         // The write holds the lock, yet reading does not block.
+    ///         assert_eq!(db.read(|n| *n).await, 1);
+    ///         commit.send(()).unwrap();
+    ///     };
+    ///     futures::join!(write, read);
+    ///
+    ///     assert_eq!(db.read(|n| *n).await, 2);
+    /// }
+    /// ```
+    pub fn new_snapshotting(data: D, backend: B) -> Self
+    where
+        D: Clone,
+        F: Default,
+    {
+        let snapshot = Snapshot::new(&data, D::clone);
+        Self {
+            snapshot: Some(snapshot),
+            ..Self::from_parts(data, backend)
+        }
+    }
+
+    /// Create a database by decoding the given bytes, paired with the given backend. The bytes
+    /// are not written to the backend; the backend is only used when saving or reloading.
+    ///
+    /// # Errors
+    ///
+    /// If the [formatter](crate::format::Format) cannot decode the bytes, an error variant is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> =
+    ///         Database::load_from_bytes(b"[1, 2, 3]".to_vec(), Memory::default()).unwrap();
+    ///     assert_eq!(db.read(Vec::clone).await, vec![1, 2, 3]);
+    ///
+    ///     let db: Result<Database<Vec<u32>, _, Json>, _> =
+    ///         Database::load_from_bytes(b"[1, 2".to_vec(), Memory::default());
+    ///     assert!(db.is_err());
+    /// });
+    /// ```
+    pub fn load_from_bytes(bytes: Vec<u8>, backend: B) -> Result<Self, KoitError>
+    where
+        F: Default,
+    {
+        let format = F::default();
+        let data = format
+            .from_bytes(bytes)
+            .map_err(|err| KoitError::FromFormat(err.into()))?;
+        Ok(Self::from_parts_with_format(data, backend, format))
+    }
+
+    /// Returns an error if the database was [finalized](crate::Database::finalize).
+    fn ensure_not_finalized(&self) -> Result<(), KoitError> {
+        if self.finalized.load(Ordering::SeqCst) {
+            Err(KoitError::Finalized)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Record a write of the given data. This must be called while the data is write-locked.
+    fn record_write(&self, data: &D) {
+        self.version.fetch_add(1, Ordering::SeqCst);
+        if let Some(snapshot) = &self.snapshot {
+            snapshot.commit(data);
         }
     }
 
@@ -91,8 +314,97 @@ where
     where
         T: FnOnce(&mut D) -> R,
     {
-        let mut data = self.data.write().await;
-        task(&mut data)
+        let mut data = self.lock_write().await;
+        let result = task(&mut data);
+        self.record_write(&data);
+        drop(data);
+        self.notifier.notify();
+        result
+    }
+
+    /// Same as [`crate::Database::write`], except it fails once the database is
+    /// [finalized](crate::Database::finalize), rather than changing data that can no longer be
+    /// saved.
+    ///
+    /// # Errors
+    ///
+    /// If the database was finalized, [`KoitError::Finalized`](crate::KoitError::Finalized) is
+    /// returned and the task is not run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, KoitError, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<u32, _, Json> = Database::from_parts(0, Memory::default());
+    ///     db.try_write(|n| *n = 1).await.unwrap();
+    ///
+    ///     db.finalize().await.unwrap();
+    ///     let result = db.try_write(|n| *n = 2).await;
+    ///     assert!(matches!(result, Err(KoitError::Finalized)));
+    ///     assert_eq!(db.read(|n| *n).await, 1);
+    /// });
+    /// ```
+    pub async fn try_write<T, R>(&self, task: T) -> Result<R, KoitError>
+    where
+        T: FnOnce(&mut D) -> R,
+    {
+        self.try_write_versioned(task)
+            .await
+            .map(|(result, _)| result)
+    }
+
+    /// Same as [`try_write`](crate::Database::try_write), also returning the version of the data
+    /// the write resulted in, for undoing it with `undo_write`.
+    pub(crate) async fn try_write_versioned<T, R>(&self, task: T) -> Result<(R, u64), KoitError>
+    where
+        T: FnOnce(&mut D) -> R,
+    {
+        let mut data = self.lock_write().await;
+        self.ensure_not_finalized()?;
+        let result = task(&mut data);
+        self.record_write(&data);
+        let version = self.version.load(Ordering::SeqCst);
+        drop(data);
+        self.notifier.notify();
+        Ok((result, version))
+    }
+
+    /// Undo a write that resulted in the given version, by running `task`, unless the data was
+    /// written to since. If the data was saved right before the write, the undo restores the
+    /// saved data, so it is marked as saved again.
+    #[cfg(feature = "json-format")]
+    pub(crate) async fn undo_write<T>(&self, version: u64, task: T)
+    where
+        T: FnOnce(&mut D),
+    {
+        let mut data = self.lock_write().await;
+        if self.version.load(Ordering::SeqCst) != version {
+            return;
+        }
+        task(&mut data);
+        self.record_write(&data);
+        if self.saved_version.load(Ordering::SeqCst) == version - 1 {
+            self.saved_version
+                .store(self.version.load(Ordering::SeqCst), Ordering::SeqCst);
+        }
+        drop(data);
+        self.notifier.notify();
+    }
+
+    /// Same as [`crate::Database::write`], and afterwards sends the event to subscribers of its
+    /// type. See [`subscribe_typed`](crate::Database::subscribe_typed).
+    ///
+    /// If nothing subscribed to events of type `E`, the event is dropped.
+    pub async fn write_with_event<T, R, E>(&self, task: T, event: E) -> R
+    where
+        T: FnOnce(&mut D) -> R,
+        E: Clone + Send + 'static,
+    {
+        let result = self.write(task).await;
+        self.notifier.emit(event);
+        result
     }
 
     /// Same as [`crate::Database::write`], except the task returns a future.
@@ -101,146 +413,2482 @@ where
         T: FnOnce(&mut D) -> Fut,
         Fut: Future<Output = R>,
     {
-        let mut data = self.data.write().await;
-        task(&mut data).await
+        let mut data = self.lock_write().await;
+        let result = task(&mut data).await;
+        self.record_write(&data);
+        drop(data);
+        self.notifier.notify();
+        result
+    }
+
+    /// Write to the data contained in the database, then read from it without letting other
+    /// writers in between. The value the write task returns is passed on to the read task, whose
+    /// return value is passed on as the return value of this function.
+    ///
+    /// This write-locks the data structure for the write task, then atomically downgrades the
+    /// lock to a read lock for the read task, so other readers can proceed while expensive
+    /// read-only work is done. Subscribers are notified of the write before the read task runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), Memory::default());
+    ///
+    ///     let sum = db
+    ///         .write_then_read(
+    ///             |numbers| numbers.extend(1..=4),
+    ///             |numbers, ()| {
+    ///                 // Other readers are let in during the read phase.
+    ///                 assert!(db.get_data_lock().try_read().is_ok());
+    ///                 numbers.iter().sum::<u32>()
+    ///             },
+    ///         )
+    ///         .await;
+    ///     assert_eq!(sum, 10);
+    /// });
+    /// ```
+    pub async fn write_then_read<W, R, T, U>(&self, write_task: W, read_task: R) -> U
+    where
+        W: FnOnce(&mut D) -> T,
+        R: FnOnce(&D, T) -> U,
+    {
+        let mut data = self.lock_write().await;
+        let written = write_task(&mut data);
+        self.record_write(&data);
+        let data = data.downgrade();
+        self.notifier.notify();
+        read_task(&data, written)
+    }
+
+    /// Read from the data contained in the database to make a decision, then write to it based on
+    /// that decision, without letting other writers in between. The value the read task returns
+    /// is passed on to the write task, whose return value is passed on as the return value of this
+    /// function.
+    ///
+    /// This write-locks the data structure once, for both tasks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// const MAX: u32 = 10;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Arc<Database<u32, _, Json>> = Arc::new(Database::from_parts(0, Memory::default()));
+    ///
+    ///     // A bounded increment: many tasks race to increment, but never past the maximum.
+    ///     let tasks: Vec<_> = (0..50)
+    ///         .map(|_| {
+    ///             let db = Arc::clone(&db);
+    ///             tokio::spawn(async move {
+    ///                 db.read_then_write(|n| *n < MAX, |n, below_max| {
+    ///                     if below_max {
+    ///                         *n += 1;
+    ///                     }
+    ///                     below_max
+    ///                 })
+    ///                 .await
+    ///             })
+    ///         })
+    ///         .collect();
+    ///
+    ///     let mut incremented = 0;
+    ///     for task in tasks {
+    ///         if task.await.unwrap() {
+    ///             incremented += 1;
+    ///         }
+    ///     }
+    ///     assert_eq!(incremented, MAX);
+    ///     assert_eq!(db.read(|n| *n).await, MAX);
+    /// }
+    /// ```
+    pub async fn read_then_write<RD, WR, T, R>(&self, read_task: RD, write_task: WR) -> R
+    where
+        RD: FnOnce(&D) -> T,
+        WR: FnOnce(&mut D, T) -> R,
+    {
+        let mut data = self.lock_write().await;
+        let decision = read_task(&data);
+        let result = write_task(&mut data, decision);
+        self.record_write(&data);
+        drop(data);
+        self.notifier.notify();
+        result
     }
 
     /// Read the data contained in the database. Many readers can read in parallel.
     /// The value your closure returns will be passed on as the return value of this function.
     ///
-    /// This read-locks the data structure.
+    /// This read-locks the data structure, unless the database is
+    /// [snapshotting](crate::Database::new_snapshotting).
     pub async fn read<T, R>(&self, task: T) -> R
     where
         T: FnOnce(&D) -> R,
     {
-        let data = self.data.read().await;
+        if let Some(snapshot) = &self.snapshot {
+            return task(&snapshot.get());
+        }
+        let data = self.lock_read().await;
         task(&data)
     }
 
-    /// Same as [`crate::Database::read`], except the task returns a future.
-    pub async fn read_and_then<T, Fut, R>(&self, task: T) -> R
-    where
-        T: FnOnce(&D) -> Fut,
-        Fut: Future<Output = R>,
-    {
+    /// Read-lock the data structure, recording the time spent waiting if enabled.
+    async fn lock_read(&self) -> tokio::sync::RwLockReadGuard<'_, D> {
+        #[cfg(feature = "lock-stats")]
+        let start = Instant::now();
         let data = self.data.read().await;
-        task(&data).await
+        #[cfg(feature = "lock-stats")]
+        self.timings
+            .lock()
+            .unwrap()
+            .lock_wait
+            .record(start.elapsed());
+        data
     }
 
-    /// Replace the actual data in the database by the given data in the parameter, returning the
-    /// old data.
-    ///
-    /// This write-locks the data structure.
-    pub async fn replace(&self, data: D) -> D {
-        self.write(|actual_data| std::mem::replace(actual_data, data))
-            .await
+    /// Write-lock the data structure, recording the time spent waiting if enabled.
+    async fn lock_write(&self) -> tokio::sync::RwLockWriteGuard<'_, D> {
+        #[cfg(feature = "lock-stats")]
+        let start = Instant::now();
+        let data = self.data.write().await;
+        #[cfg(feature = "lock-stats")]
+        self.timings
+            .lock()
+            .unwrap()
+            .lock_wait
+            .record(start.elapsed());
+        data
     }
 
-    /// Returns a reference to the underlying data lock.
-    ///
-    /// It is recommended to use the `read` and `write` methods instead of this, to ensure
-    /// locks are only held for as long as needed.
+    /// Same as [`read`](crate::Database::read), except the task returns an option, and the given
+    /// default is returned if it is `None`.
     ///
     /// # Examples
     ///
     /// ```
     /// use koit::{Database, format::Json, backend::Memory};
+    /// use serde::{Deserialize, Serialize};
     ///
-    /// type Messages = Vec<String>;
-    /// let db: Database<_, _, Json> = Database::from_parts(1, Memory::default());
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Profile {
+    ///     nickname: Option<String>,
+    /// }
     ///
-    /// futures::executor::block_on(async move {
-    ///     let data_lock = db.get_data_lock();
-    ///     let mut data = data_lock.write().await;
-    ///     *data = 42;
-    ///     drop(data);
+    /// futures::executor::block_on(async {
+    ///     let db: Database<_, _, Json> =
+    ///         Database::from_parts(Profile { nickname: None }, Memory::default());
+    ///     let nickname = |profile: &Profile| profile.nickname.clone();
+    ///     assert_eq!(db.read_or(nickname, "anonymous".to_owned()).await, "anonymous");
     ///
-    ///     db.read(|n| assert_eq!(*n, 42)).await;
+    ///     db.write(|profile| profile.nickname = Some("koit".to_owned())).await;
+    ///     assert_eq!(db.read_or(nickname, "anonymous".to_owned()).await, "koit");
     /// });
     /// ```
-    pub fn get_data_lock(&self) -> &RwLock<D> {
-        &self.data
+    pub async fn read_or<T, R>(&self, task: T, default: R) -> R
+    where
+        T: FnOnce(&D) -> Option<R>,
+    {
+        self.read(task).await.unwrap_or(default)
     }
 
-    /// Returns a mutable reference to the underlying data.
+    /// Same as [`read`](crate::Database::read), meant for extracting several parts of the data at
+    /// once. Doing all extractions in one task takes the lock once, and guarantees they observe
+    /// the same version of the data, where separate reads may see writes in between.
     ///
-    /// This borrows `Database` mutably; no locking takes place.
+    /// See [`read_2`](crate::Database::read_2) and [`read_3`](crate::Database::read_3) to
+    /// compose separate extractions.
+    pub async fn read_many<T, R>(&self, task: T) -> R
+    where
+        T: FnOnce(&D) -> R,
+    {
+        self.read(task).await
+    }
+
+    /// Run two read tasks under a single acquisition of the lock, returning both results.
+    pub async fn read_2<T1, T2, R1, R2>(&self, first: T1, second: T2) -> (R1, R2)
+    where
+        T1: FnOnce(&D) -> R1,
+        T2: FnOnce(&D) -> R2,
+    {
+        self.read(|data| (first(data), second(data))).await
+    }
+
+    /// Run three read tasks under a single acquisition of the lock, returning all results.
     ///
     /// # Examples
     ///
     /// ```
     /// use koit::{Database, format::Json, backend::Memory};
+    /// use serde::{Deserialize, Serialize};
     ///
-    /// let mut db: Database<_, _, Json> = Database::from_parts(1, Memory::default());
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Profile {
+    ///     name: String,
+    ///     age: u8,
+    ///     tags: Vec<String>,
+    /// }
     ///
-    /// let n = db.get_data_mut();
-    /// *n += 41;
+    /// futures::executor::block_on(async {
+    ///     let profile = Profile { name: "koit".to_owned(), age: 3, tags: vec!["db".to_owned()] };
+    ///     let db: Database<_, _, Json> = Database::from_parts(profile, Memory::default());
     ///
-    /// futures::executor::block_on(db.read(|n| assert_eq!(*n, 42)));
+    ///     let (name, age, tags) = db
+    ///         .read_3(
+    ///             |profile| profile.name.clone(),
+    ///             |profile| profile.age,
+    ///             |profile| profile.tags.len(),
+    ///         )
+    ///         .await;
+    ///     assert_eq!((name.as_str(), age, tags), ("koit", 3, 1));
+    /// });
     /// ```
-    pub fn get_data_mut(&mut self) -> &mut D {
-        self.data.get_mut()
+    pub async fn read_3<T1, T2, T3, R1, R2, R3>(
+        &self,
+        first: T1,
+        second: T2,
+        third: T3,
+    ) -> (R1, R2, R3)
+    where
+        T1: FnOnce(&D) -> R1,
+        T2: FnOnce(&D) -> R2,
+        T3: FnOnce(&D) -> R3,
+    {
+        self.read(|data| (first(data), second(data), third(data)))
+            .await
     }
 
-    /// Flush the data contained in the database to the backend.
+    /// Read a range of the items in the database, for data such as vectors. The task is passed
+    /// the items in the range, which is clamped to the items present, so reading past the end
+    /// gives fewer or no items. This avoids cloning all data just to show a page of it.
     ///
-    /// This read-locks the data structure.
+    /// This read-locks the data structure, unless the database is
+    /// [snapshotting](crate::Database::new_snapshotting).
     ///
-    /// # Errors
+    /// # Examples
     ///
-    /// - If the data in the database failed to be encoded by the format, an error variant is returned.
-    /// - If the bytes failed to be written to the backend, an error variant is returned. This may mean
-    /// the backend is now corrupted.
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
     ///
-    /// # Panics
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> =
+    ///         Database::from_parts((0..100).collect(), Memory::default());
     ///
-    /// Some back-ends (such as [`crate::backend::File`]) might panic on some async runtimes.
-    pub async fn save(&self) -> Result<(), KoitError> {
-        let mut backend = self.backend.lock().await;
-        let data = self.data.read().await;
-        backend
-            .write(F::to_bytes(&data).map_err(|err| KoitError::ToFormat(err.into()))?)
-            .await
-            .map_err(|err| KoitError::BackendWrite(err.into()))?;
-        Ok(())
+    ///     let page = db.read_range(40..50, |items| items.to_vec()).await;
+    ///     assert_eq!(page, (40..50).collect::<Vec<_>>());
+    ///
+    ///     let last_page = db.read_range(90..110, |items| items.len()).await;
+    ///     assert_eq!(last_page, 10);
+    /// });
+    /// ```
+    pub async fn read_range<I, T, R>(&self, range: std::ops::Range<usize>, task: T) -> R
+    where
+        D: std::ops::Deref<Target = [I]>,
+        T: FnOnce(&[I]) -> R,
+    {
+        self.read(|data| {
+            let start = range.start.min(data.len());
+            let end = range.end.clamp(start, data.len());
+            task(&data[start..end])
+        })
+        .await
     }
 
-    /// Load data from the backend.
-    async fn load_from_backend(&self) -> Result<D, KoitError> {
-        let mut backend = self.backend.lock().await;
-        let bytes = backend
-            .read()
+    /// Same as [`crate::Database::read`], except the task returns a future.
+    pub async fn read_and_then<T, Fut, R>(&self, task: T) -> R
+    where
+        T: FnOnce(&D) -> Fut,
+        Fut: Future<Output = R>,
+    {
+        if let Some(snapshot) = &self.snapshot {
+            return task(&snapshot.get()).await;
+        }
+        let data = self.lock_read().await;
+        task(&data).await
+    }
+
+    /// Same as [`crate::Database::write`], except it gives up if the write lock is not acquired
+    /// within the given duration.
+    ///
+    /// The task itself is not bounded, as a synchronous closure cannot be interrupted. To bound
+    /// how long the lock is held, use an asynchronous task with
+    /// [`write_and_then_bounded`](crate::Database::write_and_then_bounded).
+    ///
+    /// Note: the timer requires this future to be executed on the Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// If the lock is not acquired in time, [`KoitError::Timeout`](crate::KoitError::Timeout) is
+    /// returned and the task is not run.
+    pub async fn write_timeout<T, R>(&self, timeout: Duration, task: T) -> Result<R, KoitError>
+    where
+        T: FnOnce(&mut D) -> R,
+    {
+        let mut data = tokio::time::timeout(timeout, self.lock_write())
             .await
-            .map_err(|err| KoitError::BackendRead(err.into()))?;
-        Ok(F::from_bytes(bytes).map_err(|err| KoitError::FromFormat(err.into()))?)
+            .map_err(|_| KoitError::Timeout)?;
+        self.ensure_not_finalized()?;
+        let result = task(&mut data);
+        self.record_write(&data);
+        drop(data);
+        self.notifier.notify();
+        Ok(result)
     }
 
-    /// Update this database with data from the backend, returning the old data.
+    /// Same as [`crate::Database::write`], bounding how long the task waits for the write lock.
     ///
-    /// This will write-lock the internal data structure.
+    /// Only the wait for the lock is bounded. The task is a synchronous closure, which cannot be
+    /// interrupted: once it runs, it holds the lock until it returns, however long that takes. To
+    /// bound how long the lock is held, use an asynchronous task with
+    /// [`write_and_then_bounded`](crate::Database::write_and_then_bounded), which is dropped
+    /// when the time is up.
+    ///
+    /// Note: the timer requires this future to be executed on the Tokio runtime.
     ///
     /// # Errors
     ///
-    /// - If the bytes from teh backend failed to be decoded by the format, an error variant is returned.
-    /// - If the bytes failed to be read by the backend, an error variant is returned.
+    /// If the lock is not acquired within `max`, [`KoitError::Timeout`](crate::KoitError::Timeout)
+    /// is returned and the task is not run.
     ///
-    /// # Panics
+    /// # Examples
     ///
-    /// Some back-ends (such as [`crate::backend::File`]) might panic on some async runtimes.
-    pub async fn reload(&self) -> Result<D, KoitError> {
-        let new_data = self.load_from_backend().await?;
-        Ok(self.replace(new_data).await)
-    }
-
-    /// Consume the database and return its data and backend.
-    pub fn into_parts(self) -> (D, B) {
-        (self.data.into_inner(), self.backend.into_inner())
+    /// ```
+    /// use std::time::Duration;
+    /// use koit::{Database, KoitError, format::Json, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Database<u32, _, Json> = Database::from_parts(0, Memory::default());
+    ///
+    ///     let read_guard = db.get_data_lock().read().await;
+    ///     let result = db.write_bounded(Duration::from_millis(10), |n| *n = 1).await;
+    ///     assert!(matches!(result, Err(KoitError::Timeout)));
+    ///     drop(read_guard);
+    ///
+    ///     // A task that runs past the bound is not interrupted.
+    ///     let result = db
+    ///         .write_bounded(Duration::from_millis(10), |n| {
+    ///             std::thread::sleep(Duration::from_millis(50));
+    ///             *n = 2;
+    ///         })
+    ///         .await;
+    ///     assert!(result.is_ok());
+    ///     assert_eq!(db.read(|n| *n).await, 2);
+    /// }
+    /// ```
+    pub async fn write_bounded<T, R>(&self, max: Duration, task: T) -> Result<R, KoitError>
+    where
+        T: FnOnce(&mut D) -> R,
+    {
+        self.write_timeout(max, task).await
     }
-}
+
+    /// Same as [`crate::Database::write_and_then`], except it gives up if acquiring the write lock
+    /// and completing the task take longer than the given duration in total. The task's future is
+    /// dropped when the time is up, releasing the lock, so a stalled task cannot block other
+    /// users of the database indefinitely.
+    ///
+    /// Changes the task made before it was dropped are kept, and observed as a write.
+    ///
+    /// Note: the timer requires this future to be executed on the Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// If the lock is not acquired, or the task does not complete in time,
+    /// [`KoitError::Timeout`](crate::KoitError::Timeout) is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use koit::{Database, KoitError, format::Json, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Database<u32, _, Json> = Database::from_parts(0, Memory::default());
+    ///
+    ///     let result = db
+    ///         .write_and_then_bounded(Duration::from_millis(10), |n| {
+    ///             *n = 1;
+    ///             tokio::time::sleep(Duration::from_secs(60))
+    ///         })
+    ///         .await;
+    ///     assert!(matches!(result, Err(KoitError::Timeout)));
+    ///
+    ///     // The lock was released.
+    ///     assert_eq!(db.read(|n| *n).await, 1);
+    ///     let result = db
+    ///         .write_and_then_bounded(Duration::from_secs(1), |n| {
+    ///             *n = 2;
+    ///             async { "done" }
+    ///         })
+    ///         .await;
+    ///     assert_eq!(result.unwrap(), "done");
+    /// }
+    /// ```
+    pub async fn write_and_then_bounded<T, Fut, R>(
+        &self,
+        max: Duration,
+        task: T,
+    ) -> Result<R, KoitError>
+    where
+        T: FnOnce(&mut D) -> Fut,
+        Fut: Future<Output = R>,
+    {
+        let deadline = tokio::time::Instant::now() + max;
+        let mut data = tokio::time::timeout_at(deadline, self.lock_write())
+            .await
+            .map_err(|_| KoitError::Timeout)?;
+        self.ensure_not_finalized()?;
+        let result = tokio::time::timeout_at(deadline, task(&mut data)).await;
+        self.record_write(&data);
+        drop(data);
+        self.notifier.notify();
+        result.map_err(|_| KoitError::Timeout)
+    }
+
+    /// Same as [`crate::Database::read`], except it gives up if the read lock is not acquired
+    /// within the given duration.
+    ///
+    /// Note: the timer requires this future to be executed on the Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// If the lock is not acquired in time, [`KoitError::Timeout`](crate::KoitError::Timeout) is
+    /// returned and the task is not run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use koit::{Database, KoitError, format::Json, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Database<u32, _, Json> = Database::from_parts(0, Memory::default());
+    ///
+    ///     let write_guard = db.get_data_lock().write().await;
+    ///     let result = db.read_timeout(Duration::from_millis(10), |n| *n).await;
+    ///     assert!(matches!(result, Err(KoitError::Timeout)));
+    ///
+    ///     drop(write_guard);
+    ///     assert_eq!(db.read_timeout(Duration::from_millis(10), |n| *n).await.unwrap(), 0);
+    /// }
+    /// ```
+    pub async fn read_timeout<T, R>(&self, timeout: Duration, task: T) -> Result<R, KoitError>
+    where
+        T: FnOnce(&D) -> R,
+    {
+        if let Some(snapshot) = &self.snapshot {
+            return Ok(task(&snapshot.get()));
+        }
+        let data = tokio::time::timeout(timeout, self.lock_read())
+            .await
+            .map_err(|_| KoitError::Timeout)?;
+        Ok(task(&data))
+    }
+
+    /// Replace the actual data in the database by the given data in the parameter, returning the
+    /// old data.
+    ///
+    /// This write-locks the data structure.
+    pub async fn replace(&self, data: D) -> D {
+        self.write(|actual_data| std::mem::replace(actual_data, data))
+            .await
+    }
+
+    /// Combine the data in the database with the given data, using the given merge function.
+    /// Unlike [`replace`](crate::Database::replace), this does not lose changes made by other
+    /// writers in the meantime, as the function is applied to the current data.
+    ///
+    /// This write-locks the data structure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let mut volumes = HashMap::new();
+    ///     volumes.insert("music".to_owned(), 5);
+    ///     volumes.insert("effects".to_owned(), 3);
+    ///     let db: Database<_, _, Json> = Database::from_parts(volumes, Memory::default());
+    ///
+    ///     let mut incoming = HashMap::new();
+    ///     incoming.insert("effects".to_owned(), 7);
+    ///     incoming.insert("voice".to_owned(), 9);
+    ///     db.merge(incoming, |volumes, incoming| volumes.extend(incoming)).await;
+    ///
+    ///     db.read(|volumes| {
+    ///         assert_eq!(volumes.len(), 3);
+    ///         assert_eq!(volumes["music"], 5);
+    ///         assert_eq!(volumes["effects"], 7);
+    ///         assert_eq!(volumes["voice"], 9);
+    ///     })
+    ///     .await;
+    /// });
+    /// ```
+    pub async fn merge<M>(&self, other: D, merge: M)
+    where
+        M: FnOnce(&mut D, D),
+    {
+        self.write(|data| merge(data, other)).await
+    }
+
+    /// Subscribe to changes made to the data. The receiver gets a notification after every
+    /// completed write, including through [`replace`](crate::Database::replace) and
+    /// [`reload`](crate::Database::reload).
+    ///
+    /// Changes made directly through the [data lock](crate::Database::get_data_lock) are not
+    /// observed. A receiver that falls too far behind will miss notifications, which it observes as
+    /// [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged).
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<()> {
+        self.notifier.subscribe()
+    }
+
+    /// Subscribe to events of type `E`, emitted by writes through
+    /// [`write_with_event`](crate::Database::write_with_event). Unlike
+    /// [`subscribe`](crate::Database::subscribe), this tells subscribers what changed.
+    ///
+    /// Events are delivered even while a [batch](crate::Database::batch) is active. A receiver
+    /// that falls too far behind will miss events, which it observes as
+    /// [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// enum Change {
+    ///     Added(u32),
+    ///     Cleared,
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), Memory::default());
+    ///     let mut changes = db.subscribe_typed::<Change>();
+    ///
+    ///     db.write_with_event(|numbers| numbers.push(7), Change::Added(7)).await;
+    ///     db.write_with_event(Vec::clear, Change::Cleared).await;
+    ///
+    ///     assert_eq!(changes.recv().await.unwrap(), Change::Added(7));
+    ///     assert_eq!(changes.recv().await.unwrap(), Change::Cleared);
+    /// }
+    /// ```
+    pub fn subscribe_typed<E>(&self) -> tokio::sync::broadcast::Receiver<E>
+    where
+        E: Clone + Send + 'static,
+    {
+        self.notifier.subscribe_typed()
+    }
+
+    /// Returns a future that resolves when the next write completes, including through
+    /// [`replace`](crate::Database::replace) and [`reload`](crate::Database::reload). Only writes
+    /// completing after this method is called are observed. Any number of tasks can await the
+    /// next write at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<u32, _, Json> = Database::from_parts(0, Memory::default());
+    ///
+    ///     let notified = db.notified();
+    ///     futures::join!(notified, db.write(|n| *n = 42));
+    /// });
+    /// ```
+    pub fn notified(&self) -> impl Future<Output = ()> {
+        let mut changes = self.subscribe();
+        async move {
+            // Any outcome, including lagging behind or the database being dropped, means the
+            // wait is over.
+            let _ = changes.recv().await;
+        }
+    }
+
+    /// Wait until the data satisfies the predicate. The predicate is checked right away, and
+    /// again after every completed write, until it holds.
+    ///
+    /// Writes that are not [observed](crate::Database::subscribe) by subscribers, such as those
+    /// made directly through the [data lock](crate::Database::get_data_lock), do not cause the
+    /// predicate to be rechecked.
+    ///
+    /// This read-locks the data structure each time the predicate is checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Arc<Database<u32, _, Json>> = Arc::new(Database::from_parts(0, Memory::default()));
+    ///
+    ///     let incrementer = {
+    ///         let db = Arc::clone(&db);
+    ///         tokio::spawn(async move {
+    ///             for _ in 0..10 {
+    ///                 db.write(|n| *n += 1).await;
+    ///                 tokio::task::yield_now().await;
+    ///             }
+    ///         })
+    ///     };
+    ///
+    ///     db.wait_until(|n| *n >= 5).await;
+    ///     assert!(db.read(|n| *n).await >= 5);
+    ///     incrementer.await.unwrap();
+    /// }
+    /// ```
+    pub async fn wait_until<P>(&self, predicate: P)
+    where
+        P: Fn(&D) -> bool,
+    {
+        // Subscribe before checking, so a write completing in between is not missed.
+        let mut changes = self.subscribe();
+        while !self.read(&predicate).await {
+            // Lagging behind only means several writes completed; either way, check again.
+            let _ = changes.recv().await;
+        }
+    }
+
+    /// Coalesce change notifications. Until the returned guard is dropped, writes do not notify
+    /// [subscribers](crate::Database::subscribe); instead, a single notification is sent when the
+    /// guard is dropped, provided any writes happened in the meantime.
+    ///
+    /// The guard suppresses notifications for all writes to the database, including those
+    /// performed by other tasks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// let db: Database<u32, _, Json> = Database::from_parts(0, Memory::default());
+    /// let mut changes = db.subscribe();
+    ///
+    /// futures::executor::block_on(async {
+    ///     let batch = db.batch();
+    ///     db.write(|n| *n += 1).await;
+    ///     db.write(|n| *n += 1).await;
+    ///     db.write(|n| *n += 1).await;
+    ///     drop(batch);
+    /// });
+    ///
+    /// assert!(changes.try_recv().is_ok());
+    /// assert!(changes.try_recv().is_err());
+    /// ```
+    pub fn batch(&self) -> BatchGuard<'_> {
+        self.notifier.batch()
+    }
+
+    /// Returns a reference to the underlying data lock.
+    ///
+    /// It is recommended to use the `read` and `write` methods instead of this, to ensure
+    /// locks are only held for as long as needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// type Messages = Vec<String>;
+    /// let db: Database<_, _, Json> = Database::from_parts(1, Memory::default());
+    ///
+    /// futures::executor::block_on(async move {
+    ///     let data_lock = db.get_data_lock();
+    ///     let mut data = data_lock.write().await;
+    ///     *data = 42;
+    ///     drop(data);
+    ///
+    ///     db.read(|n| assert_eq!(*n, 42)).await;
+    /// });
+    /// ```
+    pub fn get_data_lock(&self) -> &RwLock<D> {
+        &self.data
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// This borrows `Database` mutably; no locking takes place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// let mut db: Database<_, _, Json> = Database::from_parts(1, Memory::default());
+    ///
+    /// let n = db.get_data_mut();
+    /// *n += 41;
+    ///
+    /// futures::executor::block_on(db.read(|n| assert_eq!(*n, 42)));
+    /// ```
+    pub fn get_data_mut(&mut self) -> &mut D {
+        self.data.get_mut()
+    }
+
+    /// Create an independent copy of this database with a different backend. The copy starts out
+    /// with a snapshot of the current data; nothing is written to the new backend until it is
+    /// saved.
+    ///
+    /// This read-locks the data structure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1], Memory::default());
+    ///     let fork = db.fork(Memory::default()).await;
+    ///
+    ///     fork.write(|numbers| numbers.push(2)).await;
+    ///
+    ///     db.read(|numbers| assert_eq!(numbers, &[1])).await;
+    ///     fork.read(|numbers| assert_eq!(numbers, &[1, 2])).await;
+    /// });
+    /// ```
+    pub async fn fork<B2>(&self, backend: B2) -> Database<D, B2, F>
+    where
+        B2: Backend,
+        D: Clone,
+        F: Clone,
+    {
+        let data = self.lock_read().await;
+        Database::from_parts_with_format(data.clone(), backend, self.format.clone())
+    }
+
+    /// Convert the data contained in the database to a [`serde_json::Value`], regardless of the
+    /// database's format. This is useful for generic tooling that inspects the data without
+    /// knowing its concrete type.
+    ///
+    /// This read-locks the data structure.
+    ///
+    /// # Errors
+    ///
+    /// If the data in the database failed to be converted, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Bincode, backend::Memory};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Data {
+    ///     cats: u64,
+    /// }
+    ///
+    /// let db: Database<_, _, Bincode> = Database::from_parts(Data { cats: 3 }, Memory::default());
+    ///
+    /// let value = futures::executor::block_on(db.to_json_value()).unwrap();
+    /// assert_eq!(value["cats"], 3);
+    /// ```
+    #[cfg(feature = "json-format")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+    pub async fn to_json_value(&self) -> Result<serde_json::Value, KoitError>
+    where
+        D: serde::Serialize,
+    {
+        let data = self.lock_read().await;
+        serde_json::to_value(&*data).map_err(|err| KoitError::ToFormat(err.into()))
+    }
+
+    /// Apply a JSON Merge Patch, as defined by
+    /// [RFC 7386](https://datatracker.ietf.org/doc/html/rfc7386), to the data contained in the
+    /// database. The data is converted to JSON, patched, and converted back, regardless of the
+    /// database's format.
+    ///
+    /// This write-locks the data structure.
+    ///
+    /// # Errors
+    ///
+    /// If the data failed to be converted to JSON, or the patched JSON does not fit the data's
+    /// type, an error variant is returned. The data is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    /// use serde::{Deserialize, Serialize};
+    /// use serde_json::json;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     volume: u8,
+    ///     name: String,
+    ///     tags: Vec<String>,
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let config = Config { volume: 5, name: "koit".to_owned(), tags: vec!["a".to_owned()] };
+    ///     let db: Database<_, _, Json> = Database::from_parts(config, Memory::default());
+    ///
+    ///     db.apply_merge_patch(json!({ "volume": 11 })).await.unwrap();
+    ///     db.read(|config| {
+    ///         assert_eq!(config.volume, 11);
+    ///         assert_eq!(config.name, "koit");
+    ///         assert_eq!(config.tags, &["a"]);
+    ///     })
+    ///     .await;
+    ///
+    ///     assert!(db.apply_merge_patch(json!({ "volume": "loud" })).await.is_err());
+    ///     db.read(|config| assert_eq!(config.volume, 11)).await;
+    /// });
+    /// ```
+    #[cfg(feature = "json-format")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+    pub async fn apply_merge_patch(&self, patch: serde_json::Value) -> Result<(), KoitError>
+    where
+        D: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.modify_json_value(|value| {
+            patch::merge_patch(value, patch);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Apply a JSON Patch, as defined by [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902),
+    /// to the data contained in the database. The data is converted to JSON, patched, and
+    /// converted back, regardless of the database's format.
+    ///
+    /// This write-locks the data structure.
+    ///
+    /// # Errors
+    ///
+    /// - If an operation of the patch fails, including a failed `test` operation,
+    ///   [`KoitError::Patch`](crate::KoitError::Patch) is returned.
+    /// - If the data failed to be converted to JSON, or the patched JSON does not fit the data's
+    ///   type, an error variant is returned.
+    ///
+    /// The patch is applied atomically: on error, the data is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, KoitError, format::Json, backend::Memory, patch::Patch};
+    /// use serde::{Deserialize, Serialize};
+    /// use serde_json::json;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     volume: u8,
+    ///     name: String,
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let config = Config { volume: 5, name: "koit".to_owned() };
+    ///     let db: Database<_, _, Json> = Database::from_parts(config, Memory::default());
+    ///
+    ///     let patch: Patch = serde_json::from_value(json!([
+    ///         { "op": "replace", "path": "/volume", "value": 11 },
+    ///     ]))
+    ///     .unwrap();
+    ///     db.apply_json_patch(&patch).await.unwrap();
+    ///     db.read(|config| assert_eq!(config.volume, 11)).await;
+    ///
+    ///     let patch: Patch = serde_json::from_value(json!([
+    ///         { "op": "replace", "path": "/name", "value": "other" },
+    ///         { "op": "test", "path": "/volume", "value": 5 },
+    ///     ]))
+    ///     .unwrap();
+    ///     let result = db.apply_json_patch(&patch).await;
+    ///     assert!(matches!(result, Err(KoitError::Patch(_))));
+    ///     db.read(|config| assert_eq!(config.name, "koit")).await;
+    /// });
+    /// ```
+    #[cfg(feature = "json-format")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+    pub async fn apply_json_patch(&self, patch: &patch::Patch) -> Result<(), KoitError>
+    where
+        D: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.modify_json_value(|value| {
+            patch
+                .apply(value)
+                .map_err(|err| KoitError::Patch(err.into()))
+        })
+        .await
+    }
+
+    /// Modify the data contained in the database as JSON. The data is only replaced, and the
+    /// write recorded, if modifying and converting back succeed.
+    #[cfg(feature = "json-format")]
+    async fn modify_json_value<M>(&self, modify: M) -> Result<(), KoitError>
+    where
+        D: serde::Serialize + serde::de::DeserializeOwned,
+        M: FnOnce(&mut serde_json::Value) -> Result<(), KoitError>,
+    {
+        let mut data = self.lock_write().await;
+        self.ensure_not_finalized()?;
+        let mut value =
+            serde_json::to_value(&*data).map_err(|err| KoitError::ToFormat(err.into()))?;
+        modify(&mut value)?;
+        *data = serde_json::from_value(value).map_err(|err| KoitError::FromFormat(err.into()))?;
+        self.record_write(&data);
+        drop(data);
+        self.notifier.notify();
+        Ok(())
+    }
+
+    /// Compute the number of bytes the data contained in the database encodes to, without
+    /// touching the backend.
+    ///
+    /// This read-locks the data structure.
+    ///
+    /// # Errors
+    ///
+    /// If the data in the database failed to be encoded by the format, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::{Format, Json}, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1, 2], Memory::default());
+    ///     let len = db.serialized_len().await.unwrap();
+    ///     assert_eq!(len, Json.to_bytes(&vec![1, 2]).unwrap().len());
+    ///
+    ///     db.write(|numbers| numbers.push(3)).await;
+    ///     assert!(db.serialized_len().await.unwrap() > len);
+    /// });
+    /// ```
+    pub async fn serialized_len(&self) -> Result<usize, KoitError> {
+        let data = self.lock_read().await;
+        let bytes = self
+            .format
+            .to_bytes(&data)
+            .map_err(|err| KoitError::ToFormat(err.into()))?;
+        Ok(bytes.len())
+    }
+
+    /// Encode the data contained in the database to a string, without touching the backend.
+    /// This is useful for logging and responses, and is available for
+    /// [text formats](crate::format::TextFormat).
+    ///
+    /// This read-locks the data structure.
+    ///
+    /// # Errors
+    ///
+    /// If the data in the database failed to be encoded by the format, or the format produced
+    /// bytes that are not valid UTF-8 despite being a text format, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     name: String,
+    ///     volume: u8,
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let config = Config { name: "koit".to_owned(), volume: 11 };
+    ///     let db: Database<_, _, Json> = Database::from_parts(config, Memory::default());
+    ///
+    ///     let text = db.to_string().await.unwrap();
+    ///     assert_eq!(text, "{\n  \"name\": \"koit\",\n  \"volume\": 11\n}");
+    /// });
+    /// ```
+    pub async fn to_string(&self) -> Result<String, KoitError>
+    where
+        F: format::TextFormat<D>,
+    {
+        let data = self.lock_read().await;
+        let bytes = self
+            .format
+            .to_bytes(&data)
+            .map_err(|err| KoitError::ToFormat(err.into()))?;
+        String::from_utf8(bytes).map_err(|err| KoitError::ToFormat(err.into()))
+    }
+
+    /// Read the bytes currently stored by the backend, without decoding them. This is useful to
+    /// inspect or hash exactly what was persisted.
+    ///
+    /// This does not lock the data structure.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes failed to be read by the backend, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::{Format, Json}, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1, 2], Memory::default());
+    ///     db.save().await.unwrap();
+    ///
+    ///     let bytes = db.backend_bytes().await.unwrap();
+    ///     assert_eq!(bytes, Json.to_bytes(&vec![1, 2]).unwrap());
+    /// });
+    /// ```
+    pub async fn backend_bytes(&self) -> Result<Vec<u8>, KoitError> {
+        self.backend
+            .lock()
+            .await
+            .read()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))
+    }
+
+    /// Compare the bytes stored by the backend with the bytes the data contained in the database
+    /// encodes to, previewing what a [save](crate::Database::save) would change. Returns the
+    /// stored bytes and the encoded bytes, in that order, if they differ, or `None` if saving
+    /// would leave the backend unchanged.
+    ///
+    /// This read-locks the data structure.
+    ///
+    /// # Errors
+    ///
+    /// - If the bytes failed to be read by the backend, an error variant is returned.
+    /// - If the data in the database failed to be encoded by the format, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1], Memory::default());
+    ///     db.save().await.unwrap();
+    ///     assert_eq!(db.pending_diff().await.unwrap(), None);
+    ///
+    ///     db.write(|numbers| numbers.push(2)).await;
+    ///     let (stored, encoded) = db.pending_diff().await.unwrap().unwrap();
+    ///     assert_eq!(stored, b"[\n  1\n]");
+    ///     assert_eq!(encoded, b"[\n  1,\n  2\n]");
+    ///
+    ///     db.save().await.unwrap();
+    ///     assert_eq!(db.pending_diff().await.unwrap(), None);
+    /// });
+    /// ```
+    pub async fn pending_diff(&self) -> Result<Option<(Vec<u8>, Vec<u8>)>, KoitError> {
+        let mut backend = self.backend.lock().await;
+        let stored = backend
+            .read()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+        let data = self.lock_read().await;
+        let encoded = self
+            .format
+            .to_bytes(&data)
+            .map_err(|err| KoitError::ToFormat(err.into()))?;
+        if stored == encoded {
+            Ok(None)
+        } else {
+            Ok(Some((stored, encoded)))
+        }
+    }
+
+    /// Write the data contained in the database to the file at the given path, without touching
+    /// the backend. This is useful for exports and backups.
+    ///
+    /// The data is written to a uniquely named temporary file next to the target, which is then
+    /// renamed into place, so the target is either left untouched or fully replaced. Concurrent
+    /// dumps to the same target do not interfere: the last rename wins.
+    ///
+    /// This read-locks the data structure.
+    ///
+    /// # Errors
+    ///
+    /// - If the data in the database failed to be encoded by the format, an error variant is returned.
+    /// - If the file failed to be written or renamed into place, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::{Format, Json}, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-dump-to-path.json");
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1, 2], Memory::default());
+    ///
+    ///     db.dump_to_path(&path).await?;
+    ///     let dumped: Vec<u32> = Json.from_bytes(std::fs::read(&path)?)?;
+    ///     assert_eq!(dumped, vec![1, 2]);
+    ///
+    ///     // Concurrent dumps each use their own temporary file, and leave other files alone.
+    ///     let neighbour = std::env::temp_dir().join("koit-doc-dump-to-path.json.tmp");
+    ///     std::fs::write(&neighbour, "keep")?;
+    ///     let (first, second) = tokio::join!(db.dump_to_path(&path), db.dump_to_path(&path));
+    ///     first?;
+    ///     second?;
+    ///     let dumped: Vec<u32> = Json.from_bytes(std::fs::read(&path)?)?;
+    ///     assert_eq!(dumped, vec![1, 2]);
+    ///     assert_eq!(std::fs::read_to_string(&neighbour)?, "keep");
+    ///
+    ///     std::fs::remove_file(&neighbour)?;
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "file-backend")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+    pub async fn dump_to_path<P>(&self, path: P) -> Result<(), KoitError>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let data = self.lock_read().await;
+        let bytes = self
+            .format
+            .to_bytes(&data)
+            .map_err(|err| KoitError::ToFormat(err.into()))?;
+        drop(data);
+
+        let path = path.as_ref();
+        let temp_path = backend::with_unique_suffix(path, ".tmp");
+        let written = match write_synced(&temp_path, &bytes).await {
+            Ok(()) => tokio::fs::rename(&temp_path, path).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = written {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(KoitError::BackendWrite(err.into()));
+        }
+        Ok(())
+    }
+
+    /// Flush the data contained in the database to the backend.
+    ///
+    /// This read-locks the data structure while encoding it. The lock is released before the
+    /// bytes are written to the backend, so writers are not held up by slow I/O. See
+    /// [`reload`](crate::Database::reload#ordering) for how concurrent saves and reloads are
+    /// ordered.
+    ///
+    /// # Errors
+    ///
+    /// - If the data in the database failed to be encoded by the format, an error variant is returned.
+    /// - If the bytes failed to be written to the backend, an error variant is returned. This may mean
+    ///   the backend is now corrupted.
+    ///
+    /// # Panics
+    ///
+    /// Some back-ends (such as [`crate::backend::File`]) might panic on some async runtimes.
+    pub async fn save(&self) -> Result<(), KoitError> {
+        let mut backend = self.backend.lock().await;
+        self.save_to(&mut backend).await
+    }
+
+    /// Flush the data contained in the database to the given, locked, backend.
+    async fn save_to(&self, backend: &mut B) -> Result<(), KoitError> {
+        self.ensure_not_finalized()?;
+        self.flush_to(backend).await
+    }
+
+    /// Same as `save_to`, regardless of whether the database was
+    /// finalized.
+    async fn flush_to(&self, backend: &mut B) -> Result<(), KoitError> {
+        let (bytes, version, serialize) = self.encode_current().await?;
+        let write = async {
+            backend
+                .write(bytes)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        self.finish_save(write, serialize, Some(version)).await
+    }
+
+    /// Encode a value with the database's format, returning the bytes and the time spent
+    /// encoding.
+    fn encode<V>(&self, value: &V) -> Result<(Vec<u8>, Duration), KoitError>
+    where
+        F: Format<V>,
+    {
+        let start = Instant::now();
+        let bytes = self
+            .format
+            .to_bytes(value)
+            .map_err(|err| KoitError::ToFormat(err.into()))?;
+        Ok((bytes, start.elapsed()))
+    }
+
+    /// Encode the data contained in the database, returning the bytes, the version of the data
+    /// they hold and the time spent encoding. The data is read-locked while encoding only.
+    async fn encode_current(&self) -> Result<(Vec<u8>, u64, Duration), KoitError> {
+        let data = self.lock_read().await;
+        let version = self.version.load(Ordering::SeqCst);
+        let (bytes, serialize) = self.encode(&*data)?;
+        Ok((bytes, version, serialize))
+    }
+
+    /// Finish a save by awaiting `write`, which writes encoded data to the locked backend. Once
+    /// written, the timings of the save are recorded and, if given, `version` is marked as saved.
+    async fn finish_save<W>(
+        &self,
+        write: W,
+        serialize: Duration,
+        version: Option<u64>,
+    ) -> Result<(), KoitError>
+    where
+        W: Future<Output = Result<(), KoitError>>,
+    {
+        let start = Instant::now();
+        write.await?;
+        let io = start.elapsed();
+
+        self.timings.lock().unwrap().save = Some(SaveTiming { serialize, io });
+        if let Some(version) = version {
+            self.saved_version.store(version, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    /// Same as [`save`](crate::Database::save), except the data is cloned under the read lock,
+    /// and encoded after the lock is released. Writers then only wait for the clone, rather than
+    /// for encoding, which helps when encoding is slow compared to cloning.
+    ///
+    /// # Errors
+    ///
+    /// - If the data in the database failed to be encoded by the format, an error variant is returned.
+    /// - If the bytes failed to be written to the backend, an error variant is returned. This may mean
+    ///   the backend is now corrupted.
+    ///
+    /// # Examples
+    ///
+    /// A writer proceeds while the backend is still writing:
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use koit::{Database, format::Json, backend::Backend};
+    /// use tokio::sync::oneshot;
+    ///
+    /// /// A backend whose first write completes once `release` is sent.
+    /// struct Slow {
+    ///     release: Option<oneshot::Receiver<()>>,
+    /// }
+    ///
+    /// #[async_trait]
+    /// impl Backend for Slow {
+    ///     type Error = std::io::Error;
+    ///
+    ///     async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+    ///         Ok(Vec::new())
+    ///     }
+    ///     async fn write(&mut self, _data: Vec<u8>) -> Result<(), Self::Error> {
+    ///         if let Some(release) = self.release.take() {
+    ///             release.await.unwrap();
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (release, released) = oneshot::channel();
+    ///     let backend = Slow { release: Some(released) };
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1], backend);
+    ///
+    ///     let save = db.save_low_contention();
+    ///     let write = async {
+    ///         tokio::task::yield_now().await;
+    ///         db.write(|numbers| numbers.push(2)).await;
+    ///         release.send(()).unwrap();
+    ///     };
+    ///     let (saved, ()) = futures::join!(save, write);
+    ///     saved.unwrap();
+    ///
+    ///     // The write came after the data was captured, so it is not saved yet.
+    ///     assert!(db.unsaved_changes());
+    /// }
+    /// ```
+    pub async fn save_low_contention(&self) -> Result<(), KoitError>
+    where
+        D: Clone,
+    {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let guard = self.lock_read().await;
+        let version = self.version.load(Ordering::SeqCst);
+        let data = D::clone(&guard);
+        drop(guard);
+
+        let (bytes, serialize) = self.encode(&data)?;
+        let write = async {
+            backend
+                .write(bytes)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        self.finish_save(write, serialize, Some(version)).await
+    }
+
+    /// Flush the data contained in the database to the backend like
+    /// [`save`](crate::Database::save), reporting progress along the way. The data is encoded
+    /// first, after which it is written to the backend in chunks. After each chunk, `progress` is
+    /// called with the number of bytes written so far and the total number of bytes.
+    ///
+    /// Backends that cannot write in chunks report progress once, after all data is written. See
+    /// [`Backend::write_chunked`](crate::backend::Backend::write_chunked).
+    ///
+    /// # Errors
+    ///
+    /// - If the data in the database failed to be encoded by the format, an error variant is returned.
+    /// - If the bytes failed to be written to the backend, an error variant is returned. This may mean
+    ///   the backend is now corrupted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::Json};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-save-with-progress.json");
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+    ///     db.write(|data| data.extend(0..100_000)).await;
+    ///
+    ///     let mut reports = Vec::new();
+    ///     db.save_with_progress(|written, total| reports.push((written, total))).await?;
+    ///
+    ///     let total = std::fs::metadata(&path)?.len();
+    ///     assert!(reports.len() > 1);
+    ///     assert!(reports.iter().all(|&(_, t)| t == total));
+    ///     assert_eq!(reports.last(), Some(&(total, total)));
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn save_with_progress<P>(&self, mut progress: P) -> Result<(), KoitError>
+    where
+        B: Send,
+        P: FnMut(u64, u64) + Send,
+    {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let (bytes, version, serialize) = self.encode_current().await?;
+        let write = async {
+            backend
+                .write_chunked(bytes, SAVE_CHUNK_SIZE, &mut progress)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        self.finish_save(write, serialize, Some(version)).await
+    }
+
+    /// Flush the data contained in the database to the backend like
+    /// [`save`](crate::Database::save), and return the SHA-256 hash of the bytes written. The
+    /// hash can be stored to validate caches derived from the data.
+    ///
+    /// # Errors
+    ///
+    /// - If the data in the database failed to be encoded by the format, an error variant is returned.
+    /// - If the bytes failed to be written to the backend, an error variant is returned. This may mean
+    ///   the backend is now corrupted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1, 2], Memory::default());
+    ///     let hash = db.save_with_hash().await.unwrap();
+    ///
+    ///     // The SHA-256 hash of `[\n  1,\n  2\n]`, the JSON encoding of the data.
+    ///     let hex: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+    ///     assert_eq!(hex, "17f5ce5ea0f8711b6b20414da84373fb56176c3a3112c86c94529d3e29dacac3");
+    /// });
+    /// ```
+    pub async fn save_with_hash(&self) -> Result<[u8; 32], KoitError> {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let (bytes, version, serialize) = self.encode_current().await?;
+        let hash = sha256::digest(&bytes);
+        let write = async {
+            backend
+                .write(bytes)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        self.finish_save(write, serialize, Some(version)).await?;
+        Ok(hash)
+    }
+
+    /// Flush a projection of the data contained in the database to the backend, such as a view
+    /// without caches or secrets. The projection is encoded by the database's format, while the
+    /// data in memory keeps everything. The projection must be supported by the format, which for
+    /// the serde formats means it must implement both `Serialize` and `DeserializeOwned`.
+    ///
+    /// This is save-only: reloading decodes the backend as the full data type, so the data must
+    /// be able to restore itself from the projection, for example through serde defaults for
+    /// the fields that were left out. As the full data is not saved, the database keeps
+    /// reporting [unsaved changes](crate::Database::unsaved_changes).
+    ///
+    /// This read-locks the data structure.
+    ///
+    /// # Errors
+    ///
+    /// - If the projection failed to be encoded by the format, an error variant is returned.
+    /// - If the bytes failed to be written to the backend, an error variant is returned. This may mean
+    ///   the backend is now corrupted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::{Backend, Memory}};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Account {
+    ///     name: String,
+    ///     #[serde(default)]
+    ///     token: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct PublicAccount {
+    ///     name: String,
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let account = Account { name: "koit".to_owned(), token: "s3cret".to_owned() };
+    ///     let db: Database<_, _, Json> = Database::from_parts(account, Memory::default());
+    ///
+    ///     db.save_projection(|account| PublicAccount { name: account.name.clone() })
+    ///         .await
+    ///         .unwrap();
+    ///     db.read(|account| assert_eq!(account.token, "s3cret")).await;
+    ///
+    ///     let (_, mut backend) = db.into_parts();
+    ///     let bytes = backend.read().await.unwrap();
+    ///     let saved = String::from_utf8(bytes).unwrap();
+    ///     assert!(saved.contains("koit"));
+    ///     assert!(!saved.contains("s3cret"));
+    /// });
+    /// ```
+    pub async fn save_projection<P, V>(&self, project: P) -> Result<(), KoitError>
+    where
+        P: FnOnce(&D) -> V,
+        F: Format<V>,
+    {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let data = self.lock_read().await;
+        let (bytes, serialize) = self.encode(&project(&data))?;
+        drop(data);
+
+        let write = async {
+            backend
+                .write(bytes)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        self.finish_save(write, serialize, None).await
+    }
+
+    /// Save the database if it has [unsaved changes](crate::Database::unsaved_changes), and mark
+    /// it as finalized. This stands in for an asynchronous `Drop`, giving shared handles a
+    /// deterministic point of shutdown. Calling it again does nothing.
+    ///
+    /// Once finalized, saving, reloading and fallible writes such as
+    /// [`try_write`](crate::Database::try_write) and
+    /// [`write_timeout`](crate::Database::write_timeout) fail with
+    /// [`KoitError::Finalized`](crate::KoitError::Finalized). Infallible writes such as
+    /// [`write`](crate::Database::write), [`replace`](crate::Database::replace) and
+    /// [`merge`](crate::Database::merge) cannot report this: they still change the data in
+    /// memory, but it cannot be saved. Use `try_write` where that matters.
+    ///
+    /// # Errors
+    ///
+    /// If the database failed to be saved, an error variant is returned and the database is not
+    /// finalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use koit::{Database, KoitError, format::Json, backend::{Backend, Memory}};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), Memory::default());
+    ///     db.write(|numbers| numbers.push(1)).await;
+    ///
+    ///     db.finalize().await.unwrap();
+    ///     db.finalize().await.unwrap();
+    ///     assert!(!db.unsaved_changes());
+    ///
+    ///     let result = db.write_timeout(Duration::from_secs(1), |numbers| numbers.push(2)).await;
+    ///     assert!(matches!(result, Err(KoitError::Finalized)));
+    ///     let result = db.try_write(|numbers| numbers.push(2)).await;
+    ///     assert!(matches!(result, Err(KoitError::Finalized)));
+    ///     assert!(matches!(db.save().await, Err(KoitError::Finalized)));
+    ///
+    ///     let (data, mut backend) = db.into_parts();
+    ///     assert_eq!(data, vec![1]);
+    ///     assert_eq!(backend.read().await.unwrap(), b"[\n  1\n]");
+    /// }
+    /// ```
+    pub async fn finalize(&self) -> Result<(), KoitError> {
+        let mut backend = self.backend.lock().await;
+        if self.finalized.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        // Fallible writes check the flag under the data lock, so once the lock is taken no
+        // such write is in flight, and none follows.
+        let unsaved = {
+            let _data = self.lock_read().await;
+            self.unsaved_changes()
+        };
+        if unsaved {
+            if let Err(err) = self.flush_to(&mut backend).await {
+                self.finalized.store(false, Ordering::SeqCst);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether the data has been written to since it was last saved or reloaded, or since
+    /// the database was constructed if it has not been saved yet. This stays `true` after a
+    /// failed save, so it can back an "unsaved changes" indicator.
+    ///
+    /// Changes made directly through the [data lock](crate::Database::get_data_lock) or
+    /// [`get_data_mut`](crate::Database::get_data_mut) are not tracked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use koit::{Database, format::Json, backend::Backend};
+    ///
+    /// /// A backend failing its first write.
+    /// #[derive(Default)]
+    /// struct Flaky {
+    ///     writes: u32,
+    /// }
+    ///
+    /// #[async_trait]
+    /// impl Backend for Flaky {
+    ///     type Error = std::io::Error;
+    ///
+    ///     async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+    ///         Ok(Vec::new())
+    ///     }
+    ///     async fn write(&mut self, _data: Vec<u8>) -> Result<(), Self::Error> {
+    ///         self.writes += 1;
+    ///         if self.writes == 1 {
+    ///             return Err(std::io::ErrorKind::Interrupted.into());
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<u32, _, Json> = Database::from_parts(0, Flaky::default());
+    ///     assert!(!db.unsaved_changes());
+    ///
+    ///     db.write(|n| *n = 42).await;
+    ///     assert!(db.unsaved_changes());
+    ///
+    ///     assert!(db.save().await.is_err());
+    ///     assert!(db.unsaved_changes());
+    ///
+    ///     db.save().await.unwrap();
+    ///     assert!(!db.unsaved_changes());
+    /// });
+    /// ```
+    pub fn unsaved_changes(&self) -> bool {
+        self.version.load(Ordering::SeqCst) != self.saved_version.load(Ordering::SeqCst)
+    }
+
+    /// Returns whether the database was constructed from a default or factory value, because
+    /// there was no existing data to load. This is the case when
+    /// [`load_from_path_or_else`](crate::Database::load_from_path_or_else) or
+    /// [`load_from_path_or_default`](crate::Database::load_from_path_or_default) created the file,
+    /// and can be used to detect the first run of an application.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::Json};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-initialized-from-default.json");
+    ///     let _ = std::fs::remove_file(&path);
+    ///
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+    ///     assert!(db.initialized_from_default());
+    ///
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+    ///     assert!(!db.initialized_from_default());
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn initialized_from_default(&self) -> bool {
+        self.initialized_from_default
+    }
+
+    /// Returns how long the most recent successful [`save`](crate::Database::save) spent
+    /// encoding the data and writing it to the backend, or `None` if the database has not been
+    /// saved yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use koit::{Database, SaveTiming, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1, 2, 3], Memory::default());
+    ///     assert!(db.last_save_timing().is_none());
+    ///
+    ///     db.save().await.unwrap();
+    ///     let SaveTiming { serialize, io } = db.last_save_timing().unwrap();
+    ///     assert!(serialize >= Duration::from_secs(0) && io >= Duration::from_secs(0));
+    ///
+    ///     db.reload().await.unwrap();
+    ///     assert!(db.last_reload_timing().is_some());
+    /// });
+    /// ```
+    pub fn last_save_timing(&self) -> Option<SaveTiming> {
+        self.timings.lock().unwrap().save
+    }
+
+    /// Returns how long the most recent successful [`reload`](crate::Database::reload) spent
+    /// reading from the backend and decoding the data, or `None` if the database has not been
+    /// reloaded yet.
+    pub fn last_reload_timing(&self) -> Option<ReloadTiming> {
+        self.timings.lock().unwrap().reload
+    }
+
+    /// Returns a summary of the time spent waiting to acquire the data lock, by every operation
+    /// taking it, such as reads, writes, saves and reloads. Reads served from a
+    /// [snapshot](crate::Database::new_snapshotting) do not wait, and locks taken directly
+    /// through the [data lock](crate::Database::get_data_lock) are not recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let db: Arc<Database<u32, _, Json>> = Arc::new(Database::from_parts(0, Memory::default()));
+    ///     db.read(|_| ()).await;
+    ///
+    ///     let held = db.get_data_lock().write().await;
+    ///     let read = {
+    ///         let db = Arc::clone(&db);
+    ///         tokio::spawn(async move { db.read(|n| *n).await })
+    ///     };
+    ///     tokio::time::sleep(Duration::from_millis(20)).await;
+    ///     drop(held);
+    ///     read.await.unwrap();
+    ///     db.save().await.unwrap();
+    ///
+    ///     // How long the read waited depends on scheduling, so only check that it was recorded.
+    ///     let stats = db.lock_wait_stats();
+    ///     assert!(stats.count >= 1);
+    ///     assert!(stats.max > Duration::from_secs(0));
+    ///     assert_eq!(stats.buckets.iter().sum::<u64>(), stats.count);
+    /// }
+    /// ```
+    #[cfg(feature = "lock-stats")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lock-stats")))]
+    pub fn lock_wait_stats(&self) -> LockWaitStats {
+        self.timings.lock().unwrap().lock_wait
+    }
+
+    /// Append a record to the data and save the database, for databases holding collections such
+    /// as event logs.
+    ///
+    /// The backend stays locked for the whole operation, so no other save comes in between. This
+    /// write-locks, and then read-locks, the data structure.
+    ///
+    /// # Errors
+    ///
+    /// - If the database was [finalized](crate::Database::finalize),
+    ///   [`KoitError::Finalized`](crate::KoitError::Finalized) is returned and the record is not
+    ///   appended.
+    /// - If the database failed to be saved, an error variant is returned. The record remains
+    ///   appended in memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<String>, _, Json> = Database::from_parts(Vec::new(), Memory::default());
+    ///
+    ///     db.push_and_save("started".to_owned()).await.unwrap();
+    ///     db.push_and_save("stopped".to_owned()).await.unwrap();
+    ///
+    ///     db.replace(Vec::new()).await;
+    ///     db.reload().await.unwrap();
+    ///     db.read(|events| assert_eq!(events, &["started", "stopped"])).await;
+    /// });
+    /// ```
+    pub async fn push_and_save<E>(&self, record: E) -> Result<(), KoitError>
+    where
+        D: Extend<E>,
+    {
+        let mut backend = self.backend.lock().await;
+        self.try_write(|data| data.extend(std::iter::once(record)))
+            .await?;
+        self.save_to(&mut backend).await
+    }
+
+    /// Increment the selected field of the data by one, wrapping around on overflow, and save
+    /// the database. Returns the new value.
+    ///
+    /// The backend stays locked for the whole operation, and the data stays locked until it is
+    /// encoded, so no other write or save can come in between the increment and the save.
+    /// Readers are let in once the field is incremented.
+    ///
+    /// # Errors
+    ///
+    /// If the database failed to be saved, an error variant is returned. The field remains
+    /// incremented in memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::{Backend, Memory}};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct Stats {
+    ///     requests: i64,
+    ///     errors: i64,
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Stats, _, Json> = Database::from_parts(Stats::default(), Memory::default());
+    ///
+    ///     assert_eq!(db.incr_and_save(|stats| &mut stats.requests).await.unwrap(), 1);
+    ///     assert_eq!(db.incr_and_save(|stats| &mut stats.requests).await.unwrap(), 2);
+    ///     assert_eq!(db.incr_and_save(|stats| &mut stats.errors).await.unwrap(), 1);
+    ///
+    ///     let (_, mut backend) = db.into_parts();
+    ///     let saved: serde_json::Value = serde_json::from_slice(&backend.read().await.unwrap()).unwrap();
+    ///     assert_eq!(saved["requests"], 2);
+    ///     assert_eq!(saved["errors"], 1);
+    /// });
+    /// ```
+    pub async fn incr_and_save<S>(&self, select: S) -> Result<i64, KoitError>
+    where
+        S: FnOnce(&mut D) -> &mut i64,
+    {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let mut data = self.lock_write().await;
+        let field = select(&mut data);
+        *field = field.wrapping_add(1);
+        let value = *field;
+        self.record_write(&data);
+        let version = self.version.load(Ordering::SeqCst);
+        let data = data.downgrade();
+        self.notifier.notify();
+
+        let (bytes, serialize) = self.encode(&*data)?;
+        drop(data);
+        let write = async {
+            backend
+                .write(bytes)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        self.finish_save(write, serialize, Some(version)).await?;
+        Ok(value)
+    }
+
+    /// Check that the backend is reachable, without changing the data it holds, for example for
+    /// readiness probes. See [`Backend::health_check`](crate::backend::Backend::health_check).
+    ///
+    /// This does not lock the data structure.
+    ///
+    /// # Errors
+    ///
+    /// If the backend is unreachable, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, FileDatabase, format::Json, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), Memory::default());
+    ///     assert!(db.health_check().await.is_ok());
+    ///
+    ///     let path = std::env::temp_dir().join("koit-doc-health-check.json");
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+    ///     assert!(db.health_check().await.is_ok());
+    ///
+    ///     // Files next to the backing file are left alone.
+    ///     let neighbour = std::env::temp_dir().join("koit-doc-health-check.json.health");
+    ///     std::fs::write(&neighbour, "keep")?;
+    ///     assert!(db.health_check().await.is_ok());
+    ///     assert_eq!(std::fs::read_to_string(&neighbour)?, "keep");
+    ///
+    ///     db.close_backend().await?;
+    ///     assert!(db.health_check().await.is_err());
+    ///
+    ///     std::fs::remove_file(&neighbour)?;
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn health_check(&self) -> Result<(), KoitError>
+    where
+        B: Send,
+    {
+        self.backend
+            .lock()
+            .await
+            .health_check()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))
+    }
+
+    /// Returns whether the backend holds any data. See
+    /// [`Backend::exists`](crate::backend::Backend::exists).
+    ///
+    /// This does not lock the data structure.
+    ///
+    /// # Errors
+    ///
+    /// If the backend failed to be queried, an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(Vec::new(), Memory::default());
+    ///     assert!(!db.backend_has_data().await.unwrap());
+    ///
+    ///     db.save().await.unwrap();
+    ///     assert!(db.backend_has_data().await.unwrap());
+    /// });
+    /// ```
+    pub async fn backend_has_data(&self) -> Result<bool, KoitError>
+    where
+        B: Send,
+    {
+        self.backend
+            .lock()
+            .await
+            .exists()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))
+    }
+
+    /// Replace the backend by the given backend, returning the old one. The data contained in
+    /// the database is first saved to the new backend, so it holds the data from the start.
+    ///
+    /// The backend stays locked for the whole operation, so no save or reload can come in
+    /// between. This read-locks the data structure while saving.
+    ///
+    /// # Errors
+    ///
+    /// If the data failed to be saved to the new backend, an error variant is returned, and the
+    /// old backend is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::Json, backend::{Backend, File}};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let old_path = std::env::temp_dir().join("koit-doc-replace-backend-old.json");
+    ///     let new_path = std::env::temp_dir().join("koit-doc-replace-backend-new.json");
+    ///     let _ = std::fs::remove_file(&old_path);
+    ///     let _ = std::fs::remove_file(&new_path);
+    ///
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&old_path).await?;
+    ///     db.write(|numbers| numbers.push(1)).await;
+    ///     db.save().await?;
+    ///
+    ///     let (new, _) = File::from_path_or_create(&new_path).await?;
+    ///     let mut old = db.replace_backend(new).await?;
+    ///     assert_eq!(old.read().await?, std::fs::read(&new_path)?);
+    ///     assert_eq!(db.backend_bytes().await?, b"[\n  1\n]");
+    ///
+    ///     std::fs::remove_file(&old_path)?;
+    ///     std::fs::remove_file(&new_path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn replace_backend(&self, mut new: B) -> Result<B, KoitError> {
+        let mut backend = self.backend.lock().await;
+        self.save_to(&mut new).await?;
+        Ok(std::mem::replace(&mut *backend, new))
+    }
+
+    /// Load data from the backend, through a buffered reader if the backend supports it.
+    async fn load_from_backend(&self, backend: &mut B) -> Result<D, KoitError>
+    where
+        B: Send,
+        F: Sync,
+    {
+        let start = Instant::now();
+        let reader = backend
+            .read_buffered()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+        let (data, io, deserialize) = match reader {
+            Some(reader) => {
+                let io = start.elapsed();
+
+                // Reading is interleaved with decoding, so it is timed as decoding.
+                let start = Instant::now();
+                let data = self
+                    .format
+                    .from_buf_read(reader)
+                    .await
+                    .map_err(|err| match err {
+                        format::ReadError::Io(err) => KoitError::BackendRead(err.into()),
+                        format::ReadError::Format(err) => KoitError::FromFormat(err.into()),
+                    })?;
+                (data, io, start.elapsed())
+            }
+            None => {
+                let bytes = backend
+                    .read()
+                    .await
+                    .map_err(|err| KoitError::BackendRead(err.into()))?;
+                let io = start.elapsed();
+
+                let start = Instant::now();
+                let data = self
+                    .format
+                    .from_bytes(bytes)
+                    .map_err(|err| KoitError::FromFormat(err.into()))?;
+                (data, io, start.elapsed())
+            }
+        };
+
+        self.timings.lock().unwrap().reload = Some(ReloadTiming { io, deserialize });
+        Ok(data)
+    }
+
+    /// Update this database with data from the backend, returning the old data.
+    ///
+    /// If the backend supports [buffered reading](crate::backend::Backend::read_buffered), the
+    /// data is decoded through [`Format::from_buf_read`](crate::format::Format::from_buf_read)
+    /// rather than read into memory first.
+    ///
+    /// This will write-lock the internal data structure.
+    ///
+    /// # Ordering
+    ///
+    /// Saves and reloads are performed one at a time: each holds exclusive access to the backend
+    /// from reading or writing the bytes until the database's data is replaced or encoded. A
+    /// reload therefore never replaces the data with bytes that were overwritten by a concurrent
+    /// save, and a save never writes data that is about to be replaced by a concurrent reload.
+    /// Both acquire access to the backend before locking the data structure, so they cannot
+    /// deadlock with each other.
+    ///
+    /// # Errors
+    ///
+    /// - If the bytes from the backend failed to be decoded by the format, an error variant is returned.
+    /// - If the bytes failed to be read by the backend, an error variant is returned.
+    /// - If the database was [finalized](crate::Database::finalize),
+    ///   [`KoitError::Finalized`](crate::KoitError::Finalized) is returned.
+    ///
+    /// # Panics
+    ///
+    /// Some back-ends (such as [`crate::backend::File`]) might panic on some async runtimes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use koit::{Database, format::Json, backend::Memory};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // The invariant is that both counters are always equal.
+    ///     let db: Arc<Database<(u32, u32), _, Json>> =
+    ///         Arc::new(Database::from_parts((0, 0), Memory::default()));
+    ///     db.save().await.unwrap();
+    ///
+    ///     let writer = tokio::spawn({
+    ///         let db = db.clone();
+    ///         async move {
+    ///             for _ in 0..100 {
+    ///                 db.write(|(a, b)| {
+    ///                     *a += 1;
+    ///                     *b += 1;
+    ///                 })
+    ///                 .await;
+    ///                 db.save().await.unwrap();
+    ///             }
+    ///         }
+    ///     });
+    ///     let reloader = tokio::spawn({
+    ///         let db = db.clone();
+    ///         async move {
+    ///             for _ in 0..100 {
+    ///                 db.reload().await.unwrap();
+    ///                 db.read(|(a, b)| assert_eq!(a, b)).await;
+    ///             }
+    ///         }
+    ///     });
+    ///     writer.await.unwrap();
+    ///     reloader.await.unwrap();
+    ///
+    ///     db.reload().await.unwrap();
+    ///     db.read(|(a, b)| assert_eq!(a, b)).await;
+    /// }
+    /// ```
+    pub async fn reload(&self) -> Result<D, KoitError>
+    where
+        B: Send,
+        F: Sync,
+    {
+        self.reload_preserving(|_, _| {}).await
+    }
+
+    /// Same as [`reload`](crate::Database::reload), except `preserve` is called with the old and
+    /// the newly loaded data before the new data replaces the old. This allows carrying over
+    /// runtime-only state that the backend does not hold.
+    ///
+    /// This write-locks the data structure while `preserve` runs.
+    ///
+    /// # Errors
+    ///
+    /// - If the bytes failed to be read by the backend, an error variant is returned.
+    /// - If the bytes from the backend failed to be decoded by the format, an error variant is
+    ///   returned.
+    /// - If the database was [finalized](crate::Database::finalize),
+    ///   [`KoitError::Finalized`](crate::KoitError::Finalized) is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Json, backend::Memory};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     volume: u8,
+    ///     #[serde(skip)]
+    ///     requests: u64,
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let config = Config { volume: 5, requests: 0 };
+    ///     let db: Database<_, _, Json> =
+    ///         Database::from_parts(config, Memory::from(r#"{"volume": 11}"#));
+    ///     db.write(|config| config.requests += 3).await;
+    ///
+    ///     db.reload_preserving(|old, new| new.requests = old.requests)
+    ///         .await
+    ///         .unwrap();
+    ///     db.read(|config| {
+    ///         assert_eq!(config.volume, 11);
+    ///         assert_eq!(config.requests, 3);
+    ///     })
+    ///     .await;
+    /// });
+    /// ```
+    pub async fn reload_preserving<M>(&self, preserve: M) -> Result<D, KoitError>
+    where
+        B: Send,
+        F: Sync,
+        M: FnOnce(&D, &mut D),
+    {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let mut new_data = self.load_from_backend(&mut backend).await?;
+
+        let mut data = self.lock_write().await;
+        preserve(&data, &mut new_data);
+        let old_data = std::mem::replace(&mut *data, new_data);
+        self.record_write(&data);
+        self.saved_version
+            .store(self.version.load(Ordering::SeqCst), Ordering::SeqCst);
+        drop(data);
+        self.notifier.notify();
+        Ok(old_data)
+    }
+
+    /// Same as [`reload`](crate::Database::reload), except decoding is skipped if the bytes in
+    /// the backend are the same as when this method last loaded them, and the data has not been
+    /// written to since. Returns whether the data was reloaded.
+    ///
+    /// The bytes are compared by their SHA-256 hash. This saves decoding large, rarely changing
+    /// data, but the backend is still read in full.
+    ///
+    /// # Errors
+    ///
+    /// - If the bytes failed to be read by the backend, an error variant is returned.
+    /// - If the bytes from the backend failed to be decoded by the format, an error variant is
+    ///   returned.
+    /// - If the database was [finalized](crate::Database::finalize),
+    ///   [`KoitError::Finalized`](crate::KoitError::Finalized) is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use koit::{Database, format::Custom, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let decodes = Arc::new(AtomicUsize::new(0));
+    ///     let format = Custom::new(serde_json::to_vec::<Vec<u32>>, {
+    ///         let decodes = decodes.clone();
+    ///         move |bytes: Vec<u8>| {
+    ///             decodes.fetch_add(1, Ordering::SeqCst);
+    ///             serde_json::from_slice(&bytes)
+    ///         }
+    ///     });
+    ///     let db = Database::from_parts_with_format(Vec::new(), Memory::from("[1, 2]"), format);
+    ///
+    ///     assert!(db.reload_if_changed().await.unwrap());
+    ///     assert!(!db.reload_if_changed().await.unwrap());
+    ///     assert_eq!(decodes.load(Ordering::SeqCst), 1);
+    ///
+    ///     // Writes are discarded by reloading, like with `reload`.
+    ///     db.write(|numbers| numbers.push(3)).await;
+    ///     assert!(db.reload_if_changed().await.unwrap());
+    ///     assert_eq!(db.read(Vec::clone).await, vec![1, 2]);
+    ///     assert_eq!(decodes.load(Ordering::SeqCst), 2);
+    /// });
+    /// ```
+    pub async fn reload_if_changed(&self) -> Result<bool, KoitError> {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let start = Instant::now();
+        let bytes = backend
+            .read()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+        let io = start.elapsed();
+
+        let hash = sha256::digest(&bytes);
+        let version = self.version.load(Ordering::SeqCst);
+        if *self.loaded_hash.lock().unwrap() == Some((hash, version)) {
+            return Ok(false);
+        }
+
+        let start = Instant::now();
+        let new_data = self
+            .format
+            .from_bytes(bytes)
+            .map_err(|err| KoitError::FromFormat(err.into()))?;
+        let deserialize = start.elapsed();
+        self.timings.lock().unwrap().reload = Some(ReloadTiming { io, deserialize });
+
+        let mut data = self.lock_write().await;
+        *data = new_data;
+        self.record_write(&data);
+        let version = self.version.load(Ordering::SeqCst);
+        self.saved_version.store(version, Ordering::SeqCst);
+        *self.loaded_hash.lock().unwrap() = Some((hash, version));
+        drop(data);
+        self.notifier.notify();
+        Ok(true)
+    }
+
+    /// Consume the database and return its data and backend.
+    pub fn into_parts(self) -> (D, B) {
+        (self.data.into_inner(), self.backend.into_inner())
+    }
+
+    /// Save the database, then consume it and return its data and backend.
+    ///
+    /// # Errors
+    ///
+    /// If the database failed to be saved, the database is returned intact alongside the error,
+    /// so the save can be retried.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use async_trait::async_trait;
+    /// use koit::{Database, format::Json, backend::Backend};
+    ///
+    /// /// A backend failing its first write.
+    /// #[derive(Default)]
+    /// struct Flaky {
+    ///     writes: u32,
+    ///     data: Vec<u8>,
+    /// }
+    ///
+    /// #[async_trait]
+    /// impl Backend for Flaky {
+    ///     type Error = std::io::Error;
+    ///
+    ///     async fn read(&mut self) -> Result<Vec<u8>, Self::Error> {
+    ///         Ok(self.data.clone())
+    ///     }
+    ///     async fn write(&mut self, data: Vec<u8>) -> Result<(), Self::Error> {
+    ///         self.writes += 1;
+    ///         if self.writes == 1 {
+    ///             return Err(std::io::ErrorKind::Interrupted.into());
+    ///         }
+    ///         self.data = data;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Json> = Database::from_parts(vec![1, 2], Flaky::default());
+    ///
+    ///     let (db, _err) = db.try_into_parts().await.err().unwrap();
+    ///     let (data, backend) = db.try_into_parts().await.ok().unwrap();
+    ///     assert_eq!(data, vec![1, 2]);
+    ///     assert_eq!(backend.data, b"[\n  1,\n  2\n]");
+    /// });
+    /// ```
+    pub async fn try_into_parts(self) -> Result<(D, B), (Self, KoitError)> {
+        if self.finalized.load(Ordering::SeqCst) {
+            return Ok(self.into_parts());
+        }
+        match self.save().await {
+            Ok(()) => Ok(self.into_parts()),
+            Err(err) => Err((self, err)),
+        }
+    }
+
+    /// Flush the elements of the data to the backend as they are encoded, wrapped in `open` and
+    /// `close`. `encode` appends an element to a chunk, and is told whether it is the first.
+    #[cfg(feature = "json-format")]
+    async fn save_elements<T, E>(
+        &self,
+        open: &'static [u8],
+        close: &'static [u8],
+        mut encode: E,
+    ) -> Result<(), KoitError>
+    where
+        B: Send,
+        D: AsRef<[T]>,
+        T: Sync,
+        E: FnMut(&mut Vec<u8>, &T, bool) -> Result<(), serde_json::Error> + Send,
+    {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let data = self.lock_read().await;
+        let version = self.version.load(Ordering::SeqCst);
+
+        let mut elements = D::as_ref(&data).iter();
+        let error = std::sync::Mutex::new(None);
+        let (mut opened, mut first, mut closed) = (false, true, false);
+        let mut chunks = std::iter::from_fn(|| {
+            if closed {
+                return None;
+            }
+            let mut chunk = Vec::with_capacity(SAVE_CHUNK_SIZE);
+            if !opened {
+                chunk.extend_from_slice(open);
+                opened = true;
+            }
+            while chunk.len() < SAVE_CHUNK_SIZE {
+                let element = match elements.next() {
+                    Some(element) => element,
+                    None => {
+                        chunk.extend_from_slice(close);
+                        closed = true;
+                        break;
+                    }
+                };
+                if let Err(err) = encode(&mut chunk, element, first) {
+                    *error.lock().unwrap() = Some(err);
+                    // Abort the write, such that the backend keeps the data it held.
+                    closed = true;
+                    return Some(Err(std::io::Error::other(
+                        "an element failed to be encoded",
+                    )));
+                }
+                first = false;
+            }
+            Some(Ok(chunk))
+        });
+        let write = async {
+            let result = backend.write_streamed(&mut chunks).await;
+            let error = error.lock().unwrap().take();
+            if let Some(err) = error {
+                return Err(KoitError::ToFormat(err.into()));
+            }
+            result.map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        // Encoding is interleaved with writing, so it is timed as part of the I/O.
+        self.finish_save(write, Duration::from_secs(0), Some(version))
+            .await
+    }
+}
+
+#[cfg(feature = "json-format")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+impl<D, B> Database<D, B, format::Json>
+where
+    B: Backend,
+    format::Json: Format<D>,
+{
+    /// Flush the data contained in the database to the backend as a JSON array, serializing it
+    /// element by element. Backends supporting [streamed
+    /// writes](crate::backend::Backend::write_streamed), such as the file backend, receive the
+    /// array in chunks as it is serialized, so it is never held in memory as a whole.
+    ///
+    /// The array is written as compact JSON, not pretty-printed like the output of
+    /// [`save`](crate::Database::save), but decodes to the same data. Streaming relies on the
+    /// layout of JSON arrays, so this is only available for [`Json`](crate::format::Json)
+    /// databases of sequences. [`Ndjson`](crate::format::Ndjson) databases have a `save_iter`
+    /// of their own, writing one element per line.
+    ///
+    /// This read-locks the data structure for the whole save.
+    ///
+    /// # Errors
+    ///
+    /// - If an element failed to be encoded, an error variant is returned. The backend is left
+    ///   holding the data it held before.
+    /// - If the bytes failed to be written to the backend, an error variant is returned. This may
+    ///   mean the backend is now corrupted; the file backend streams to a temporary file that is
+    ///   renamed into place, so its file is left as it was.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use koit::{FileDatabase, backend::File, format::Json};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-save-iter.json");
+    ///     let _ = std::fs::remove_file(&path);
+    ///
+    ///     let numbers: Vec<u32> = (0..1_000_000).collect();
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+    ///     db.replace(numbers.clone()).await;
+    ///     db.save_iter().await?;
+    ///
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path(&path).await?;
+    ///     db.read(|loaded| assert!(loaded == &numbers)).await;
+    ///
+    ///     // JSON objects need string keys, so the last element fails to encode after many
+    ///     // chunks were written. The file is left as it was.
+    ///     let saved = std::fs::read(&path)?;
+    ///     let mut maps: Vec<HashMap<(u8, u8), u8>> = vec![HashMap::new(); 100_000];
+    ///     maps.push(vec![((1, 2), 3)].into_iter().collect());
+    ///     let db = FileDatabase::<_, Json>::from_parts(maps, File::from_path(&path).await?);
+    ///     assert!(db.save_iter().await.is_err());
+    ///     assert!(std::fs::read(&path)? == saved);
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn save_iter<T>(&self) -> Result<(), KoitError>
+    where
+        B: Send,
+        D: AsRef<[T]>,
+        T: serde::Serialize + Sync,
+    {
+        self.save_elements(b"[", b"]", |chunk, element, first| {
+            if !first {
+                chunk.push(b',');
+            }
+            serde_json::to_writer(chunk, element)
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "json-format")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+impl<T, B> Database<Vec<T>, B, format::Ndjson>
+where
+    B: Backend,
+    format::Ndjson: Format<Vec<T>>,
+{
+    /// Flush the data contained in the database to the backend as newline-delimited JSON,
+    /// serializing it element by element. Backends supporting [streamed
+    /// writes](crate::backend::Backend::write_streamed), such as the file backend, receive the
+    /// lines in chunks as they are serialized, so they are never held in memory as a whole.
+    ///
+    /// The bytes are the same as those written by [`save`](crate::Database::save).
+    ///
+    /// This read-locks the data structure for the whole save.
+    ///
+    /// # Errors
+    ///
+    /// - If an element failed to be encoded, an error variant is returned. The backend is left
+    ///   holding the data it held before.
+    /// - If the bytes failed to be written to the backend, an error variant is returned. This may
+    ///   mean the backend is now corrupted; the file backend streams to a temporary file that is
+    ///   renamed into place, so its file is left as it was.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{Database, format::Ndjson, backend::Memory};
+    ///
+    /// futures::executor::block_on(async {
+    ///     let db: Database<Vec<u32>, _, Ndjson> = Database::from_parts(vec![1, 2], Memory::default());
+    ///     db.save_iter().await.unwrap();
+    ///
+    ///     let (_, mut backend) = db.into_parts();
+    ///     assert_eq!(backend.take(), b"1\n2\n");
+    /// });
+    /// ```
+    pub async fn save_iter(&self) -> Result<(), KoitError>
+    where
+        B: Send,
+        T: serde::Serialize + Sync,
+    {
+        self.save_elements(b"", b"", |chunk, element, _| {
+            serde_json::to_writer(&mut *chunk, element)?;
+            chunk.push(b'\n');
+            Ok(())
+        })
+        .await
+    }
+}
+
+/// Decode the bytes of a backend that is expected to hold data. If the bytes are empty and fail
+/// to decode, the backend is reported as empty rather than as holding undecodable data. Formats
+/// encoding some data as no bytes at all, such as Bincode for `()`, still decode empty bytes.
+#[cfg(feature = "file-backend")]
+fn decode_expecting_data<D, F>(format: &F, bytes: Vec<u8>) -> Result<D, KoitError>
+where
+    F: Format<D>,
+{
+    let empty = bytes.is_empty();
+    format.from_bytes(bytes).map_err(|err| {
+        if empty {
+            KoitError::EmptyBackend
+        } else {
+            KoitError::FromFormat(err.into())
+        }
+    })
+}
 
 /// A file-backed database.
 ///
-/// Note: this requires its futures to be executed on the Tokio 0.3 runtime.
+/// Note: this requires its futures to be executed on the Tokio runtime.
 #[cfg(feature = "file-backend")]
 #[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
 pub type FileDatabase<D, F> = Database<D, backend::File, F>;
@@ -255,10 +2903,37 @@ where
     ///
     /// # Errors
     /// If the file cannot be read, or the [formatter](crate::format::Format) cannot decode the data,
-    /// an error variant will be returned.
+    /// an error variant will be returned. If the file is empty and the formatter cannot decode
+    /// that, [`KoitError::EmptyBackend`](crate::KoitError::EmptyBackend) is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, KoitError, format::{Bincode, Json}};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-load-empty.json");
+    ///     std::fs::write(&path, "")?;
+    ///
+    ///     let err = FileDatabase::<Vec<u32>, Json>::load_from_path(&path).await.unwrap_err();
+    ///     assert!(matches!(err, KoitError::EmptyBackend));
+    ///     let err = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path)
+    ///         .await
+    ///         .unwrap_err();
+    ///     assert!(matches!(err, KoitError::EmptyBackend));
+    ///
+    ///     // Bincode encodes `()` as no bytes at all, so the empty file holds valid data.
+    ///     assert!(FileDatabase::<(), Bincode>::load_from_path(&path).await.is_ok());
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
     pub async fn load_from_path<P>(path: P) -> Result<Self, KoitError>
     where
         P: AsRef<std::path::Path>,
+        F: Default,
     {
         let mut backend = backend::File::from_path(path)
             .await
@@ -268,13 +2943,70 @@ where
             .read()
             .await
             .map_err(|err| KoitError::BackendRead(err.into()))?;
-        let data = F::from_bytes(bytes).map_err(|err| KoitError::FromFormat(err.into()))?;
+        let format = F::default();
+        let data = decode_expecting_data(&format, bytes)?;
 
-        Ok(Database {
-            data: RwLock::new(data),
-            backend: Mutex::new(backend),
-            _format: PhantomData,
-        })
+        Ok(Database::from_parts_with_format(data, backend, format))
+    }
+
+    /// Construct the file-backed database from the newest of the given paths that can be loaded.
+    /// The candidates are tried in order of modification time, newest first, until one is
+    /// successfully read and decoded.
+    ///
+    /// # Errors
+    ///
+    /// If none of the candidates can be loaded,
+    /// [`KoitError::NoValidCandidate`](crate::KoitError::NoValidCandidate) is returned, holding
+    /// the error encountered for each candidate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use koit::{FileDatabase, format::Json};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let valid = std::env::temp_dir().join("koit-doc-newest-valid.json.bak");
+    ///     let corrupt = std::env::temp_dir().join("koit-doc-newest-valid.json");
+    ///     std::fs::write(&valid, "[1, 2, 3]")?;
+    ///     std::fs::write(&corrupt, "[1, 2, 3, 4")?;
+    ///     std::fs::File::options()
+    ///         .write(true)
+    ///         .open(&valid)?
+    ///         .set_modified(SystemTime::now() - Duration::from_secs(60))?;
+    ///
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_newest_valid(&[&corrupt, &valid]).await?;
+    ///     assert_eq!(db.read(|numbers| numbers.clone()).await, vec![1, 2, 3]);
+    ///
+    ///     std::fs::remove_file(&valid)?;
+    ///     std::fs::remove_file(&corrupt)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn load_newest_valid<P>(paths: &[P]) -> Result<Self, KoitError>
+    where
+        P: AsRef<std::path::Path>,
+        F: Default,
+    {
+        let mut errors = Vec::new();
+        let mut candidates = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            match tokio::fs::metadata(path).await.and_then(|m| m.modified()) {
+                Ok(modified) => candidates.push((modified, path)),
+                Err(err) => errors.push(KoitError::BackendCreation(err.into())),
+            }
+        }
+        candidates.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        for (_, path) in candidates {
+            match Self::load_from_path(path).await {
+                Ok(db) => return Ok(db),
+                Err(err) => errors.push(err),
+            }
+        }
+        Err(KoitError::NoValidCandidate(errors))
     }
 
     /// Construct the file-backed database from the given path. If the file does not exist,
@@ -284,37 +3016,558 @@ where
     where
         P: AsRef<std::path::Path>,
         T: FnOnce() -> D,
+        F: Default,
     {
         let (mut backend, exists) = backend::File::from_path_or_create(path)
             .await
             .map_err(|e| KoitError::BackendCreation(e.into()))?;
 
+        let format = F::default();
         let data = if exists {
             let bytes = backend
                 .read()
                 .await
                 .map_err(|err| KoitError::BackendRead(err.into()))?;
-            F::from_bytes(bytes).map_err(|err| KoitError::FromFormat(err.into()))?
+            decode_expecting_data(&format, bytes)?
         } else {
             factory()
         };
 
-        let db = Database {
-            data: RwLock::new(data),
-            backend: Mutex::new(backend),
-            _format: PhantomData,
-        };
+        let mut db = Database::from_parts_with_format(data, backend, format);
+        db.initialized_from_default = !exists;
 
         db.save().await?;
         Ok(db)
     }
 
+    /// Same as [`load_from_path_or_else`](crate::Database::load_from_path_or_else), except the
+    /// factory value is not saved. If the file does not exist, it is only created when the
+    /// database is first saved, so read-only sessions leave no file behind.
+    ///
+    /// # Errors
+    ///
+    /// If the file exists but cannot be read, or the [formatter](crate::format::Format) cannot
+    /// decode the data, an error variant will be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::Json};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-load-lazy.json");
+    ///     let _ = std::fs::remove_file(&path);
+    ///
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_else_lazy(&path, || vec![1])
+    ///         .await?;
+    ///     db.read(|numbers| assert_eq!(numbers, &[1])).await;
+    ///     assert!(!path.exists());
+    ///
+    ///     db.save().await?;
+    ///     assert_eq!(std::fs::read_to_string(&path)?, "[\n  1\n]");
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn load_from_path_or_else_lazy<P, T>(path: P, factory: T) -> Result<Self, KoitError>
+    where
+        P: AsRef<std::path::Path>,
+        T: FnOnce() -> D,
+        F: Default,
+    {
+        match backend::File::from_path(&path).await {
+            Ok(mut backend) => {
+                let format = F::default();
+                let bytes = backend
+                    .read()
+                    .await
+                    .map_err(|err| KoitError::BackendRead(err.into()))?;
+                let data = decode_expecting_data(&format, bytes)?;
+                Ok(Database::from_parts_with_format(data, backend, format))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let backend = backend::File::lazy(path.as_ref().to_owned());
+                let mut db = Database::from_parts(factory(), backend);
+                db.initialized_from_default = true;
+                Ok(db)
+            }
+            Err(err) => Err(KoitError::BackendCreation(err.into())),
+        }
+    }
+
+    /// Flush the data contained in the database to the backing file, verifying the result before
+    /// committing to it.
+    ///
+    /// The data is encoded and decoded again, checking that the decoded data equals the data in
+    /// the database. The bytes are then written to a uniquely named temporary file next to the
+    /// backing file, which is read back and compared to the encoded bytes. Only if both checks
+    /// pass is the temporary file renamed into place; otherwise, it is removed. This costs an extra decode and read over
+    /// [`save`](crate::Database::save), as well as the creation of a new file on every save.
+    ///
+    /// This read-locks the data structure while encoding and decoding, but not during I/O.
+    ///
+    /// # Errors
+    ///
+    /// - If the data in the database failed to be encoded or decoded by the format, an error
+    ///   variant is returned.
+    /// - If the temporary file failed to be written, read or renamed into place, an error variant
+    ///   is returned.
+    /// - If the decoded data does not equal the data in the database, or the bytes read back differ
+    ///   from the bytes written, [`KoitError::Validation`](crate::KoitError::Validation) is
+    ///   returned. The backing file is left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::Json};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-save-verified.json");
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+    ///
+    ///     db.write(|numbers| numbers.push(42)).await;
+    ///     db.save_verified().await?;
+    ///     assert_eq!(std::fs::read_to_string(&path)?, "[\n  42\n]");
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn save_verified(&self) -> Result<(), KoitError>
+    where
+        D: PartialEq,
+    {
+        let mut backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let data = self.lock_read().await;
+        let version = self.version.load(Ordering::SeqCst);
+        let (bytes, serialize) = self.encode(&*data)?;
+        let decoded = self
+            .format
+            .from_bytes(bytes.clone())
+            .map_err(|err| KoitError::FromFormat(err.into()))?;
+        if decoded != *data {
+            return Err(KoitError::Validation);
+        }
+        drop(data);
+
+        let temp_path = backend.unique_temp_path(".tmp");
+        let write = async {
+            write_synced(&temp_path, &bytes)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))?;
+            let written = tokio::fs::read(&temp_path)
+                .await
+                .map_err(|err| KoitError::BackendRead(err.into()))?;
+            if written != bytes {
+                return Err(KoitError::Validation);
+            }
+
+            backend
+                .replace_with(&temp_path)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        // Remove the temporary file if any step fails.
+        let write = async {
+            let result = write.await;
+            if result.is_err() {
+                let _ = tokio::fs::remove_file(&temp_path).await;
+            }
+            result
+        };
+        self.finish_save(write, serialize, Some(version)).await
+    }
+
+    /// Close the backing file, flushing any buffered writes. This does not save the database.
+    ///
+    /// Afterwards, reloading or saving the database fails with
+    /// [`FileError::Closed`](crate::backend::FileError::Closed). See
+    /// [`File::close`](crate::backend::File::close).
+    ///
+    /// # Errors
+    ///
+    /// If the file failed to be flushed, an error variant is returned. The file is closed
+    /// regardless.
+    pub async fn close_backend(&self) -> Result<(), KoitError> {
+        self.backend
+            .lock()
+            .await
+            .close()
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))
+    }
+
+    /// Check that the volume holding the backing file accepts writes, for example to fail fast
+    /// at startup on a read-only or full volume. A uniquely named scratch file is written and
+    /// flushed to disk next to the backing file, then removed. The backing file and other
+    /// existing files are not touched.
+    ///
+    /// # Errors
+    ///
+    /// If the scratch file failed to be written or removed, an error variant is returned holding
+    /// the I/O error, such as for a read-only file system or a full disk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::Json};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-assert-writable.json");
+    ///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_default(&path).await?;
+    ///
+    ///     // Files next to the backing file are left alone.
+    ///     let neighbour = std::env::temp_dir().join("koit-doc-assert-writable.json.writable");
+    ///     std::fs::write(&neighbour, "keep")?;
+    ///     db.assert_writable().await?;
+    ///     assert_eq!(std::fs::read_to_string(&neighbour)?, "keep");
+    ///     std::fs::remove_file(&neighbour)?;
+    ///     std::fs::remove_file(&path)?;
+    ///
+    ///     // No files can be created in `/proc`, not even by root.
+    ///     if cfg!(target_os = "linux") {
+    ///         let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_else_lazy(
+    ///             "/proc/koit-doc-assert-writable.json",
+    ///             Vec::new,
+    ///         )
+    ///         .await?;
+    ///         assert!(db.assert_writable().await.is_err());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn assert_writable(&self) -> Result<(), KoitError> {
+        let scratch_path = self.backend.lock().await.unique_temp_path(".writable");
+        write_synced(&scratch_path, b"koit")
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))?;
+        tokio::fs::remove_file(&scratch_path)
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))
+    }
+
     /// Same as `load_from_path_or_else`, except it uses [`Default`](`std::default::Default`) instead of a factory.
     pub async fn load_from_path_or_default<P>(path: P) -> Result<Self, KoitError>
     where
         P: AsRef<std::path::Path>,
         D: std::default::Default,
+        F: Default,
     {
         Self::load_from_path_or_else(path, || std::default::Default::default()).await
     }
 }
+
+#[cfg(all(
+    feature = "file-backend",
+    any(feature = "json-format", feature = "bincode-format")
+))]
+impl<D> FileDatabase<D, format::DynFormat>
+where
+    D: serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// Construct the file-backed database from the given path, with the format chosen by the
+    /// extension of the path. See [`format::by_extension`](crate::format::by_extension).
+    ///
+    /// # Errors
+    ///
+    /// - If no format is known for the extension,
+    ///   [`DynFormatError::UnknownExtension`](crate::format::DynFormatError::UnknownExtension) is
+    ///   returned as a [`KoitError::FromFormat`](crate::KoitError::FromFormat).
+    /// - If the file cannot be read, or the format cannot decode the data, an error variant is
+    ///   returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::{Bincode, DynFormat, Format, FormatKind}};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let dir = std::env::temp_dir();
+    ///     let json_path = dir.join("koit-doc-by-extension.json");
+    ///     let bin_path = dir.join("koit-doc-by-extension.bin");
+    ///     std::fs::write(&json_path, "[1, 2]")?;
+    ///     std::fs::write(&bin_path, Bincode.to_bytes(&vec![3u32, 4])?)?;
+    ///
+    ///     let db = FileDatabase::<Vec<u32>, DynFormat>::load_from_path_by_extension(&json_path)
+    ///         .await?;
+    ///     assert_eq!(db.read(Vec::clone).await, vec![1, 2]);
+    ///
+    ///     let db = FileDatabase::<Vec<u32>, DynFormat>::load_from_path_by_extension(&bin_path)
+    ///         .await?;
+    ///     assert_eq!(db.read(Vec::clone).await, vec![3, 4]);
+    ///     db.write(|numbers| numbers.push(5)).await;
+    ///     db.save().await?;
+    ///     let saved: Vec<u32> = Bincode.from_bytes(std::fs::read(&bin_path)?)?;
+    ///     assert_eq!(saved, vec![3, 4, 5]);
+    ///
+    ///     let txt_path = dir.join("koit-doc-by-extension.txt");
+    ///     assert!(FileDatabase::<Vec<u32>, DynFormat>::load_from_path_by_extension(&txt_path)
+    ///         .await
+    ///         .is_err());
+    ///
+    ///     std::fs::remove_file(&json_path)?;
+    ///     std::fs::remove_file(&bin_path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn load_from_path_by_extension<P>(path: P) -> Result<Self, KoitError>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let path = path.as_ref();
+        let kind = format::by_extension(path).ok_or_else(|| {
+            KoitError::FromFormat(format::DynFormatError::UnknownExtension(path.to_owned()).into())
+        })?;
+        let mut backend = backend::File::from_path(path)
+            .await
+            .map_err(|err| KoitError::BackendCreation(err.into()))?;
+
+        let bytes = backend
+            .read()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+        let format = format::DynFormat(kind);
+        let data = decode_expecting_data(&format, bytes)?;
+
+        Ok(Database::from_parts_with_format(data, backend, format))
+    }
+}
+
+#[cfg(all(feature = "file-backend", feature = "json-format"))]
+impl<D> FileDatabase<D, format::Json>
+where
+    D: serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// Same as [`load_from_path`](crate::Database::load_from_path), except the file is rejected
+    /// if it contains object fields that the data does not have, regardless of whether the data
+    /// type denies unknown fields itself. This is useful to catch misspelled configuration keys.
+    ///
+    /// A field is unknown if decoding the data ignores it. Fields the data accepts are not
+    /// reported, including fields under an [alias](https://serde.rs/field-attrs.html#alias) and
+    /// fields that are skipped when encoding. Fields the data skips when decoding are reported,
+    /// while fields collected by a [flattened](https://serde.rs/field-attrs.html#flatten) field
+    /// are not.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read, or cannot be decoded, an error variant is returned. If the file
+    /// contains an unknown field, [`KoitError::FromFormat`](crate::KoitError::FromFormat) is
+    /// returned with a decoding error naming the field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use koit::{FileDatabase, format::Json};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Deserialize, Serialize)]
+    /// struct Config {
+    ///     volume: u8,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, Serialize)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct StrictConfig {
+    ///     volume: u8,
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-load-strict.json");
+    ///     std::fs::write(&path, r#"{"volume": 11, "colour": "red"}"#)?;
+    ///
+    ///     assert!(FileDatabase::<Config, Json>::load_from_path(&path).await.is_ok());
+    ///
+    ///     let err = FileDatabase::<Config, Json>::load_from_path_strict(&path).await.unwrap_err();
+    ///     assert!(err.source().unwrap().to_string().contains("colour"));
+    ///
+    ///     let err = FileDatabase::<StrictConfig, Json>::load_from_path_strict(&path)
+    ///         .await
+    ///         .unwrap_err();
+    ///     assert!(err.source().unwrap().to_string().contains("colour"));
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Fields the data accepts are known, even if they are not encoded under that name:
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::Json};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Deserialize, Serialize)]
+    /// struct Config {
+    ///     #[serde(alias = "loudness")]
+    ///     volume: u8,
+    ///     #[serde(default, skip_serializing_if = "Option::is_none")]
+    ///     theme: Option<String>,
+    ///     #[serde(default, skip_serializing)]
+    ///     legacy: bool,
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-load-strict-known.json");
+    ///     std::fs::write(&path, r#"{"loudness": 11, "theme": null, "legacy": true}"#)?;
+    ///
+    ///     let db = FileDatabase::<Config, Json>::load_from_path_strict(&path).await?;
+    ///     db.read(|config| assert_eq!(config.volume, 11)).await;
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn load_from_path_strict<P>(path: P) -> Result<Self, KoitError>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let mut backend = backend::File::from_path(path)
+            .await
+            .map_err(|e| KoitError::BackendCreation(e.into()))?;
+        let bytes = backend
+            .read()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+
+        let value: serde_json::Value = decode_expecting_data(&format::Json, bytes)?;
+        let (data, ignored) =
+            ignored::from_value(&value).map_err(|err| KoitError::FromFormat(err.into()))?;
+        if let Some(field) = ignored {
+            let err = <serde_json::Error as serde::de::Error>::custom(format!(
+                "unknown field `{}`",
+                field
+            ));
+            return Err(KoitError::FromFormat(err.into()));
+        }
+
+        Ok(Self::from_parts(data, backend))
+    }
+
+    /// Same as [`load_from_path`](crate::Database::load_from_path), except environment variables
+    /// named `{prefix}_{FIELD}` override fields of the file before it is decoded. Nested fields
+    /// are separated by a double underscore, as in `{prefix}_{FIELD}__{SUBFIELD}`.
+    ///
+    /// Field names are matched to the file case-insensitively, and are lowercased when the file
+    /// does not have the field. Values replacing a string in the file are used as a string as is.
+    /// Other values are parsed as JSON if possible, and used as a string otherwise.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read, or the file with the environment applied cannot be decoded,
+    /// an error variant is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use koit::{FileDatabase, format::Json};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     volume: u8,
+    ///     name: String,
+    ///     audio: Audio,
+    /// }
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Audio {
+    ///     device: String,
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("koit-doc-load-with-env.json");
+    ///     std::fs::write(
+    ///         &path,
+    ///         r#"{"volume": 5, "name": "koit", "audio": {"device": "default"}}"#,
+    ///     )?;
+    ///     std::env::set_var("KOIT_DOC_VOLUME", "11");
+    ///     std::env::set_var("KOIT_DOC_NAME", "42");
+    ///     std::env::set_var("KOIT_DOC_AUDIO__DEVICE", "hw:1");
+    ///
+    ///     let db = FileDatabase::<Config, Json>::load_from_path_with_env(&path, "KOIT_DOC").await?;
+    ///     db.read(|config| {
+    ///         assert_eq!(config.volume, 11);
+    ///         // The file holds a string, so the value is not parsed as a number.
+    ///         assert_eq!(config.name, "42");
+    ///         assert_eq!(config.audio.device, "hw:1");
+    ///     })
+    ///     .await;
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn load_from_path_with_env<P>(path: P, prefix: &str) -> Result<Self, KoitError>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let mut backend = backend::File::from_path(path)
+            .await
+            .map_err(|e| KoitError::BackendCreation(e.into()))?;
+        let bytes = backend
+            .read()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+
+        let mut value: serde_json::Value = decode_expecting_data(&format::Json, bytes)?;
+        let prefix = format!("{}_", prefix);
+        for (name, raw) in std::env::vars_os() {
+            let (name, raw) = match (name.into_string(), raw.into_string()) {
+                (Ok(name), Ok(raw)) => (name, raw),
+                _ => continue,
+            };
+            match name.strip_prefix(&prefix) {
+                Some(field) if !field.is_empty() => overlay_env(&mut value, field, raw),
+                _ => {}
+            }
+        }
+        let data =
+            serde_json::from_value(value).map_err(|err| KoitError::FromFormat(err.into()))?;
+
+        Ok(Self::from_parts(data, backend))
+    }
+}
+
+/// Set the field of `value` named by an environment variable to the variable's value.
+#[cfg(all(feature = "file-backend", feature = "json-format"))]
+fn overlay_env(value: &mut serde_json::Value, field: &str, raw: String) {
+    use serde_json::Value;
+
+    let mut target = value;
+    for segment in field.split("__") {
+        if !target.is_object() {
+            *target = Value::Object(Default::default());
+        }
+        let fields = target.as_object_mut().unwrap();
+        let name = fields
+            .keys()
+            .find(|name| name.eq_ignore_ascii_case(segment))
+            .cloned()
+            .unwrap_or_else(|| segment.to_ascii_lowercase());
+        target = fields.entry(name).or_insert(Value::Null);
+    }
+    *target = match target {
+        Value::String(_) => Value::String(raw),
+        _ => serde_json::from_str(&raw).unwrap_or(Value::String(raw)),
+    };
+}
+
+/// Write the bytes to a new file at the given path, and flush them to disk.
+#[cfg(feature = "file-backend")]
+async fn write_synced(path: &std::path::Path, bytes: &[u8]) -> Result<(), std::io::Error> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut file = tokio::fs::File::create(path).await?;
+    file.write_all(bytes).await?;
+    file.sync_all().await
+}
@@ -0,0 +1,104 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use tokio::sync::broadcast;
+
+/// The number of notifications a subscriber may fall behind before it starts missing them.
+const CHANNEL_CAPACITY: usize = 16;
+
+#[derive(Debug, Default)]
+struct BatchState {
+    depth: usize,
+    pending: bool,
+}
+
+/// Notifies subscribers of changes made to the database.
+#[derive(Debug)]
+pub(crate) struct Notifier {
+    sender: broadcast::Sender<()>,
+    batch: std::sync::Mutex<BatchState>,
+    /// A `broadcast::Sender<E>` per event type `E`, created when the first subscriber of that
+    /// type subscribes.
+    events: std::sync::Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+}
+
+impl Notifier {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            batch: std::sync::Mutex::new(BatchState::default()),
+            events: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<()> {
+        self.sender.subscribe()
+    }
+
+    pub(crate) fn subscribe_typed<E>(&self) -> broadcast::Receiver<E>
+    where
+        E: Clone + Send + 'static,
+    {
+        self.events
+            .lock()
+            .unwrap()
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(broadcast::channel::<E>(CHANNEL_CAPACITY).0))
+            .downcast_ref::<broadcast::Sender<E>>()
+            .expect("event senders are keyed by their event type")
+            .subscribe()
+    }
+
+    /// Send the event to its subscribers. Events are not deferred by batches.
+    pub(crate) fn emit<E>(&self, event: E)
+    where
+        E: Clone + Send + 'static,
+    {
+        let events = self.events.lock().unwrap();
+        if let Some(sender) = events.get(&TypeId::of::<E>()) {
+            let sender = sender
+                .downcast_ref::<broadcast::Sender<E>>()
+                .expect("event senders are keyed by their event type");
+            // Sending only fails if there are no subscribers.
+            let _ = sender.send(event);
+        }
+    }
+
+    /// Notify subscribers of a change, or defer the notification if a batch is active.
+    pub(crate) fn notify(&self) {
+        let mut batch = self.batch.lock().unwrap();
+        if batch.depth > 0 {
+            batch.pending = true;
+        } else {
+            // Sending only fails if there are no subscribers.
+            let _ = self.sender.send(());
+        }
+    }
+
+    pub(crate) fn batch(&self) -> BatchGuard<'_> {
+        self.batch.lock().unwrap().depth += 1;
+        BatchGuard { notifier: self }
+    }
+}
+
+/// A guard coalescing change notifications, returned by [`Database::batch`](crate::Database::batch).
+///
+/// While the guard is alive, writes to the database do not notify subscribers. When the guard is
+/// dropped, a single notification is sent if any writes happened in the meantime.
+#[must_use = "notifications are only suppressed while the guard is alive"]
+#[derive(Debug)]
+pub struct BatchGuard<'a> {
+    notifier: &'a Notifier,
+}
+
+impl Drop for BatchGuard<'_> {
+    fn drop(&mut self) {
+        let mut batch = self.notifier.batch.lock().unwrap();
+        batch.depth -= 1;
+        if batch.depth == 0 && batch.pending {
+            batch.pending = false;
+            let _ = self.notifier.sender.send(());
+        }
+    }
+}
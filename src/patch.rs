@@ -0,0 +1,231 @@
+//! Patches applied to the data as JSON, regardless of the database's format.
+//!
+//! See [`Database::apply_merge_patch`](crate::Database::apply_merge_patch) and
+//! [`Database::apply_json_patch`](crate::Database::apply_json_patch).
+
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+
+/// A JSON Patch, as defined by [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902): a
+/// sequence of operations applied in order.
+///
+/// A patch is usually deserialized from its standard JSON representation.
+///
+/// # Examples
+///
+/// ```
+/// use koit::patch::Patch;
+/// use serde_json::json;
+///
+/// let patch: Patch = serde_json::from_value(json!([
+///     { "op": "test", "path": "/volume", "value": 5 },
+///     { "op": "replace", "path": "/volume", "value": 11 },
+///     { "op": "add", "path": "/tags/-", "value": "loud" },
+/// ]))
+/// .unwrap();
+///
+/// let mut value = json!({ "volume": 5, "tags": [] });
+/// patch.apply(&mut value).unwrap();
+/// assert_eq!(value, json!({ "volume": 11, "tags": ["loud"] }));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Patch(pub Vec<PatchOperation>);
+
+/// A single operation of a [`Patch`](crate::patch::Patch). Paths are JSON Pointers, as defined by
+/// [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901).
+#[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Add a value to an object or insert it into an array.
+    Add { path: String, value: Value },
+    /// Remove the value at the path.
+    Remove { path: String },
+    /// Replace the value at the path.
+    Replace { path: String, value: Value },
+    /// Remove the value at `from` and add it at the path.
+    Move { from: String, path: String },
+    /// Copy the value at `from` to the path.
+    Copy { from: String, path: String },
+    /// Check that the value at the path equals the given value.
+    Test { path: String, value: Value },
+}
+
+/// The error variants applying a [`Patch`](crate::patch::Patch) can return.
+#[cfg_attr(docsrs, doc(cfg(feature = "json-format")))]
+#[derive(Debug, Error)]
+pub enum PatchError {
+    /// The path is not a valid JSON Pointer.
+    #[error("invalid path `{0}`")]
+    InvalidPath(String),
+    /// There is no value at the path, or a value cannot be added there.
+    #[error("no value at path `{0}`")]
+    PathNotFound(String),
+    /// A `move` operation would move a value into itself.
+    #[error("cannot move `{from}` into its child `{path}`")]
+    MoveIntoChild { from: String, path: String },
+    /// A `test` operation failed.
+    #[error("test failed at path `{0}`")]
+    TestFailed(String),
+}
+
+impl Patch {
+    /// Apply the operations to the value in order.
+    ///
+    /// # Errors
+    ///
+    /// If an operation fails, an error variant is returned. Operations before it remain applied,
+    /// so apply the patch to a copy if it must be applied atomically.
+    pub fn apply(&self, value: &mut Value) -> Result<(), PatchError> {
+        for operation in &self.0 {
+            operation.apply(value)?;
+        }
+        Ok(())
+    }
+}
+
+impl PatchOperation {
+    fn apply(&self, target: &mut Value) -> Result<(), PatchError> {
+        match self {
+            PatchOperation::Add { path, value } => add(target, path, value.clone()),
+            PatchOperation::Remove { path } => remove(target, path).map(drop),
+            PatchOperation::Replace { path, value } => {
+                *get_mut(target, path)? = value.clone();
+                Ok(())
+            }
+            PatchOperation::Move { from, path } => {
+                if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                    return Err(PatchError::MoveIntoChild {
+                        from: from.clone(),
+                        path: path.clone(),
+                    });
+                }
+                let value = remove(target, from)?;
+                add(target, path, value)
+            }
+            PatchOperation::Copy { from, path } => {
+                let value = get_mut(target, from)?.clone();
+                add(target, path, value)
+            }
+            PatchOperation::Test { path, value } => {
+                if *get_mut(target, path)? == *value {
+                    Ok(())
+                } else {
+                    Err(PatchError::TestFailed(path.clone()))
+                }
+            }
+        }
+    }
+}
+
+/// Split a JSON Pointer into its unescaped reference tokens.
+fn tokens(path: &str) -> Result<Vec<String>, PatchError> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !path.starts_with('/') {
+        return Err(PatchError::InvalidPath(path.to_owned()));
+    }
+    Ok(path[1..]
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Returns the index an array token refers to, if it is a valid index.
+fn index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+fn get_mut<'a>(target: &'a mut Value, path: &str) -> Result<&'a mut Value, PatchError> {
+    let not_found = || PatchError::PathNotFound(path.to_owned());
+    tokens(path)?
+        .iter()
+        .try_fold(target, |target, token| match target {
+            Value::Object(fields) => fields.get_mut(token),
+            Value::Array(values) => index(token).and_then(move |index| values.get_mut(index)),
+            _ => None,
+        })
+        .ok_or_else(not_found)
+}
+
+/// Split the path into the path of the parent and the last token.
+fn split_last(path: &str) -> Result<(&str, String), PatchError> {
+    let mut tokens = tokens(path)?;
+    let last = tokens
+        .pop()
+        .ok_or_else(|| PatchError::PathNotFound(path.to_owned()))?;
+    let parent = &path[..path.rfind('/').unwrap_or(0)];
+    Ok((parent, last))
+}
+
+fn add(target: &mut Value, path: &str, value: Value) -> Result<(), PatchError> {
+    if path.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+    let (parent, last) = split_last(path)?;
+    match get_mut(target, parent)? {
+        Value::Object(fields) => {
+            fields.insert(last, value);
+            Ok(())
+        }
+        Value::Array(values) if last == "-" => {
+            values.push(value);
+            Ok(())
+        }
+        Value::Array(values) => match index(&last) {
+            Some(index) if index <= values.len() => {
+                values.insert(index, value);
+                Ok(())
+            }
+            _ => Err(PatchError::PathNotFound(path.to_owned())),
+        },
+        _ => Err(PatchError::PathNotFound(path.to_owned())),
+    }
+}
+
+fn remove(target: &mut Value, path: &str) -> Result<Value, PatchError> {
+    let (parent, last) = split_last(path)?;
+    let removed = match get_mut(target, parent)? {
+        Value::Object(fields) => fields.remove(&last),
+        Value::Array(values) => match index(&last) {
+            Some(index) if index < values.len() => Some(values.remove(index)),
+            _ => None,
+        },
+        _ => None,
+    };
+    removed.ok_or_else(|| PatchError::PathNotFound(path.to_owned()))
+}
+
+/// Apply a JSON Merge Patch, as defined by RFC 7386, to the target.
+pub(crate) fn merge_patch(target: &mut Value, patch: Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        patch => {
+            *target = patch;
+            return;
+        }
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(&key);
+            } else {
+                merge_patch(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
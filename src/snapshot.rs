@@ -0,0 +1,29 @@
+use std::sync::{Arc, Mutex};
+
+/// The last committed copy of the data, served to readers of a
+/// [snapshotting](crate::Database::new_snapshotting) database.
+#[derive(Debug)]
+pub(crate) struct Snapshot<D> {
+    current: Mutex<Arc<D>>,
+    clone: fn(&D) -> D,
+}
+
+impl<D> Snapshot<D> {
+    pub(crate) fn new(data: &D, clone: fn(&D) -> D) -> Self {
+        Self {
+            current: Mutex::new(Arc::new(clone(data))),
+            clone,
+        }
+    }
+
+    /// Returns the last committed copy. This never waits on writers.
+    pub(crate) fn get(&self) -> Arc<D> {
+        self.current.lock().unwrap().clone()
+    }
+
+    /// Replace the copy by a copy of the given data.
+    pub(crate) fn commit(&self, data: &D) {
+        let copy = Arc::new((self.clone)(data));
+        *self.current.lock().unwrap() = copy;
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+/// The time spent by a [`save`](crate::Database::save).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveTiming {
+    /// The time spent encoding the data.
+    pub serialize: Duration,
+    /// The time spent writing to the backend.
+    pub io: Duration,
+}
+
+/// The time spent by a [`reload`](crate::Database::reload).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReloadTiming {
+    /// The time spent reading from the backend.
+    pub io: Duration,
+    /// The time spent decoding the data.
+    pub deserialize: Duration,
+}
+
+/// A summary of the time [reads](crate::Database::read) and [writes](crate::Database::write)
+/// spent waiting to acquire the data lock.
+#[cfg(feature = "lock-stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "lock-stats")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockWaitStats {
+    /// The number of lock acquisitions recorded.
+    pub count: u64,
+    /// The total time spent waiting.
+    pub total: Duration,
+    /// The longest time spent waiting.
+    pub max: Duration,
+    /// The number of waits per duration range: shorter than 1µs, 10µs, 100µs, 1ms and 10ms
+    /// respectively, each bucket excluding the waits counted by the preceding ones, and the last
+    /// bucket counting all waits of 10ms or more.
+    pub buckets: [u64; 6],
+}
+
+#[cfg(feature = "lock-stats")]
+impl LockWaitStats {
+    /// The mean time spent waiting, or `None` if nothing was recorded.
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(Duration::from_nanos(
+            (self.total.as_nanos() / u128::from(self.count)) as u64,
+        ))
+    }
+
+    pub(crate) fn record(&mut self, wait: Duration) {
+        self.count += 1;
+        self.total += wait;
+        self.max = self.max.max(wait);
+        let mut bound = Duration::from_micros(1);
+        let mut bucket = 0;
+        while bucket < self.buckets.len() - 1 && wait >= bound {
+            bound *= 10;
+            bucket += 1;
+        }
+        self.buckets[bucket] += 1;
+    }
+}
+
+/// The timings of the most recent operations.
+#[derive(Debug, Default)]
+pub(crate) struct Timings {
+    pub(crate) save: Option<SaveTiming>,
+    pub(crate) reload: Option<ReloadTiming>,
+    #[cfg(feature = "lock-stats")]
+    pub(crate) lock_wait: LockWaitStats,
+}
@@ -0,0 +1,281 @@
+//! Saving several databases together.
+//!
+//! [`save_all`](crate::transaction::save_all) saves a set of databases such that either all of
+//! them or none of them are updated, for example to keep accounts and a ledger in agreement.
+//!
+//! # Examples
+//!
+//! If any of the databases fails to be saved, none of the files change:
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use koit::{FileDatabase, format::Json, transaction::save_all};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let accounts_path = std::env::temp_dir().join("koit-doc-transaction-accounts.json");
+//!     let ledger_path = std::env::temp_dir().join("koit-doc-transaction-ledger.json");
+//!
+//!     let accounts = FileDatabase::<HashMap<String, u32>, Json>::load_from_path_or_default(
+//!         &accounts_path,
+//!     )
+//!     .await?;
+//!     // JSON cannot encode maps with non-string keys, so saving the ledger will fail.
+//!     let ledger = FileDatabase::<HashMap<Vec<u8>, u32>, Json>::load_from_path_or_default(
+//!         &ledger_path,
+//!     )
+//!     .await?;
+//!
+//!     accounts.write(|accounts| accounts.insert("alice".to_owned(), 10)).await;
+//!     ledger.write(|ledger| ledger.insert(b"alice".to_vec(), 10)).await;
+//!     assert!(save_all(&[&accounts, &ledger]).await.is_err());
+//!
+//!     assert_eq!(std::fs::read_to_string(&accounts_path)?, "{}");
+//!     assert_eq!(std::fs::read_to_string(&ledger_path)?, "{}");
+//!
+//!     ledger.write(|ledger| ledger.clear()).await;
+//!     save_all(&[&accounts, &ledger]).await?;
+//!     assert_eq!(
+//!         std::fs::read_to_string(&accounts_path)?,
+//!         "{\n  \"alice\": 10\n}"
+//!     );
+//!
+//!     std::fs::remove_file(&accounts_path)?;
+//!     std::fs::remove_file(&ledger_path)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::MutexGuard;
+
+use crate::backend::File;
+use crate::{FileDatabase, Format, KoitError};
+
+/// A database that can take part in [`save_all`](crate::transaction::save_all).
+///
+/// Saving is split in steps, such that all databases can be prepared before any of them is
+/// changed. The trait is object safe, so databases of different types can be saved together.
+#[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+#[async_trait]
+pub trait SaveAny: Send + Sync {
+    /// Lock the backing storage, then encode the data and write it next to the storage, without
+    /// replacing the storage yet. The storage stays locked until the returned save is dropped,
+    /// so no other save or reload comes in between the steps.
+    async fn stage<'a>(&'a self) -> Result<Box<dyn Staged + 'a>, KoitError>;
+}
+
+/// A save prepared by [`SaveAny::stage`](crate::transaction::SaveAny::stage), holding the lock
+/// on the backing storage.
+#[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+#[async_trait]
+pub trait Staged: Send {
+    /// Copy the backing storage aside, so the commit can be rolled back. If there is no backing
+    /// storage yet, nothing is copied.
+    async fn back_up(&mut self) -> Result<(), KoitError>;
+
+    /// Replace the backing storage by the staged data, and mark that data as saved.
+    async fn commit(&mut self) -> Result<(), KoitError>;
+
+    /// Undo the commit by restoring the backing storage from the backup. If there was no
+    /// backing storage, it is removed again.
+    async fn roll_back(&mut self) -> Result<(), KoitError>;
+
+    /// Remove the files left over by the save, such as the backup, ignoring failures.
+    async fn clean_up(&mut self);
+}
+
+#[async_trait]
+impl<D, F> SaveAny for FileDatabase<D, F>
+where
+    D: Send + Sync,
+    F: Format<D> + Send + Sync,
+{
+    async fn stage<'a>(&'a self) -> Result<Box<dyn Staged + 'a>, KoitError> {
+        let backend = self.backend.lock().await;
+        self.ensure_not_finalized()?;
+        let data = self.lock_read().await;
+        let version = self.version.load(Ordering::SeqCst);
+        let (bytes, serialize) = self.encode(&*data)?;
+        drop(data);
+
+        let path = backend.unique_temp_path(".tmp");
+        crate::write_synced(&path, &bytes)
+            .await
+            .map_err(|err| KoitError::BackendWrite(err.into()))?;
+        Ok(Box::new(StagedFile {
+            db: self,
+            backend,
+            path,
+            version,
+            serialize,
+            saved_version: self.saved_version.load(Ordering::SeqCst),
+            backup: None,
+            committed: false,
+        }))
+    }
+}
+
+/// A staged save of a [`FileDatabase`](crate::FileDatabase).
+struct StagedFile<'a, D, F> {
+    db: &'a FileDatabase<D, F>,
+    backend: MutexGuard<'a, File>,
+    /// The path of the staged data.
+    path: PathBuf,
+    /// The version of the staged data.
+    version: u64,
+    /// The time spent encoding the staged data.
+    serialize: Duration,
+    /// The version saved before the commit, restored when rolling back.
+    saved_version: u64,
+    backup: Option<PathBuf>,
+    committed: bool,
+}
+
+#[async_trait]
+impl<'a, D, F> Staged for StagedFile<'a, D, F>
+where
+    D: Send + Sync,
+    F: Format<D> + Send + Sync,
+{
+    async fn back_up(&mut self) -> Result<(), KoitError> {
+        self.backup = self
+            .backend
+            .back_up()
+            .await
+            .map_err(|err| KoitError::BackendRead(err.into()))?;
+        Ok(())
+    }
+
+    async fn commit(&mut self) -> Result<(), KoitError> {
+        let (backend, path) = (&mut self.backend, &self.path);
+        let write = async {
+            backend
+                .replace_with(path)
+                .await
+                .map_err(|err| KoitError::BackendWrite(err.into()))
+        };
+        self.db
+            .finish_save(write, self.serialize, Some(self.version))
+            .await?;
+        self.committed = true;
+        Ok(())
+    }
+
+    async fn roll_back(&mut self) -> Result<(), KoitError> {
+        let restored = match self.backup.take() {
+            Some(backup) => self.backend.replace_with(&backup).await,
+            None => self.backend.remove().await,
+        };
+        restored.map_err(|err| KoitError::BackendWrite(err.into()))?;
+        self.db
+            .saved_version
+            .store(self.saved_version, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn clean_up(&mut self) {
+        if !self.committed {
+            let _ = tokio::fs::remove_file(&self.path).await;
+        }
+        if let Some(backup) = self.backup.take() {
+            let _ = tokio::fs::remove_file(backup).await;
+        }
+    }
+}
+
+/// Save all given databases, such that either all of them or none of them are updated.
+///
+/// The data of every database is first encoded and written to a temporary file. Only if all of
+/// them succeed are the temporary files renamed into place. Should renaming fail part-way, the
+/// databases already updated are rolled back from backups taken beforehand.
+///
+/// The backends of the databases stay locked from encoding until renaming, so no other save or
+/// reload comes in between. They are locked in a fixed order, such that concurrent calls cannot
+/// deadlock. The data is only locked while it is encoded, so concurrent writes may be picked up
+/// by some databases but not by others.
+///
+/// # Errors
+///
+/// If any database failed to be encoded, written or renamed into place, the first error is
+/// returned. Rolling back is best-effort: if it fails too, the original error is still returned.
+///
+/// # Examples
+///
+/// Databases without a backing file yet take part as well:
+///
+/// ```
+/// use koit::{FileDatabase, format::Json, transaction::save_all};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = std::env::temp_dir().join("koit-doc-save-all-lazy.json");
+///     let _ = std::fs::remove_file(&path);
+///
+///     let db = FileDatabase::<Vec<u32>, Json>::load_from_path_or_else_lazy(&path, || vec![1])
+///         .await?;
+///     assert!(!path.exists());
+///
+///     save_all(&[&db, &db]).await?;
+///     assert_eq!(std::fs::read_to_string(&path)?, "[\n  1\n]");
+///     assert!(!db.unsaved_changes());
+///
+///     std::fs::remove_file(&path)?;
+///     Ok(())
+/// }
+/// ```
+///
+/// See also the [transaction module documentation](crate::transaction).
+#[cfg_attr(docsrs, doc(cfg(feature = "file-backend")))]
+pub async fn save_all(databases: &[&dyn SaveAny]) -> Result<(), KoitError> {
+    let mut databases = databases.to_vec();
+    databases.sort_by_key(|database| address(*database));
+    databases.dedup_by_key(|database| address(*database));
+
+    let mut staged = Vec::with_capacity(databases.len());
+    for database in databases {
+        match database.stage().await {
+            Ok(save) => staged.push(save),
+            Err(err) => {
+                clean_up(&mut staged).await;
+                return Err(err);
+            }
+        }
+    }
+
+    for save in &mut staged {
+        if let Err(err) = save.back_up().await {
+            clean_up(&mut staged).await;
+            return Err(err);
+        }
+    }
+
+    for committed in 0..staged.len() {
+        if let Err(err) = staged[committed].commit().await {
+            for save in &mut staged[..committed] {
+                let _ = save.roll_back().await;
+            }
+            clean_up(&mut staged).await;
+            return Err(err);
+        }
+    }
+
+    clean_up(&mut staged).await;
+    Ok(())
+}
+
+/// The address of the database, which orders the databases when locking them.
+fn address(database: &dyn SaveAny) -> usize {
+    database as *const dyn SaveAny as *const () as usize
+}
+
+/// Clean up after the given saves.
+async fn clean_up(staged: &mut [Box<dyn Staged + '_>]) {
+    for save in staged {
+        save.clean_up().await;
+    }
+}